@@ -1,19 +1,36 @@
+use crate::cli::focus::{notify_phase_end, spawn_notifier};
+use crate::cli::remind::{notify_reminder, reminder_text};
 use crate::db::{
-    models::{Task, TaskPriority, TaskStatus, UserProfile},
+    models::{
+        FocusConfig, FocusTimer, Recurrence, Task, TaskCommit, TaskPriority, TaskStatus, TimeEntry,
+        UserProfile,
+    },
     Database,
 };
+use crate::inputs::quick_add::{extract_reward, parse_tags, QuickAdd};
+use crate::inputs::search::SearchQuery;
+use crate::interop::git;
 use crate::ui::theme::ThemeType;
-use chrono::{DateTime, Utc};
+use crate::utils::{format_due, format_duration, format_local, parse_due_date, timezone};
+use chrono::{DateTime, Duration, Months, NaiveDate, Utc};
 use color_eyre::eyre::Result;
-use notify_rust::Notification;
 use ratatui::widgets::{ListState, TableState};
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
 use tui_textarea::TextArea;
 
+/// How much `+` adds to the current focus phase.
+const FOCUS_EXTEND_SEC: i64 = 5 * 60;
+
+/// How often the TUI looks for due reminders.
+const REMINDER_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
 #[derive(PartialEq)]
 pub enum InputMode {
     Normal,
     Editing,
     Search,
+    Checklist,
 }
 
 #[derive(PartialEq, Clone, Copy, Debug)]
@@ -21,8 +38,11 @@ pub enum CurrentView {
     Splash,
     Dashboard,
     Kanban,
+    Calendar,
     Focus,
     Analytics,
+    Archive,
+    Trash,
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -31,6 +51,9 @@ pub enum FormField {
     Priority,
     XP,
     DueDate,
+    Repeat,
+    Project,
+    Tags,
     Description,
 }
 
@@ -40,6 +63,9 @@ pub struct TaskForm<'a> {
     pub priority: TaskPriority,
     pub xp: TextArea<'a>,
     pub due_date: TextArea<'a>,
+    pub recurrence: TextArea<'a>,
+    pub project: TextArea<'a>,
+    pub tags: TextArea<'a>,
     pub active_field: FormField,
 }
 
@@ -56,7 +82,16 @@ impl<'a> Default for TaskForm<'a> {
         xp.insert_str("10");
 
         let mut due_date = TextArea::default();
-        due_date.set_placeholder_text("YYYY-MM-DD");
+        due_date.set_placeholder_text("tomorrow 9am");
+
+        let mut recurrence = TextArea::default();
+        recurrence.set_placeholder_text("weekly:mon,wed");
+
+        let mut project = TextArea::default();
+        project.set_placeholder_text("backend");
+
+        let mut tags = TextArea::default();
+        tags.set_placeholder_text("bug, ui");
 
         Self {
             title,
@@ -64,26 +99,36 @@ impl<'a> Default for TaskForm<'a> {
             priority: TaskPriority::Medium,
             xp,
             due_date,
+            recurrence,
+            project,
+            tags,
             active_field: FormField::Title,
         }
     }
 }
 
-pub struct FocusState {
-    pub is_running: bool,
-    pub duration_sec: i64,
-    pub remaining_sec: i64,
-    pub last_tick: Option<DateTime<Utc>>,
-}
+impl TaskForm<'_> {
+    /// What the due field resolves to (`None` when it's empty), or the message
+    /// shown under it when it can't be read.
+    pub fn parsed_due_date(&self) -> Result<Option<DateTime<Utc>>, String> {
+        let input = self.due_date.lines().join(" ");
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(None);
+        }
+        parse_due_date(input)
+            .map(Some)
+            .ok_or_else(|| format!("Can't read '{}'", input))
+    }
 
-impl Default for FocusState {
-    fn default() -> Self {
-        Self {
-            is_running: false,
-            duration_sec: 25 * 60,
-            remaining_sec: 25 * 60,
-            last_tick: None,
+    /// The repeat rule (`None` when the field is empty), or why it can't be
+    /// read.
+    pub fn parsed_recurrence(&self) -> Result<Option<Recurrence>, String> {
+        let input = self.recurrence.lines().join("");
+        if input.trim().is_empty() {
+            return Ok(None);
         }
+        input.parse().map(Some)
     }
 }
 
@@ -94,6 +139,16 @@ pub struct KanbanState {
     pub focused_col: usize,
 }
 
+impl KanbanState {
+    pub fn col_state(&mut self, col: usize) -> &mut ListState {
+        match col {
+            0 => &mut self.todo_state,
+            1 => &mut self.doing_state,
+            _ => &mut self.done_state,
+        }
+    }
+}
+
 impl Default for KanbanState {
     fn default() -> Self {
         let mut s = Self {
@@ -109,6 +164,33 @@ impl Default for KanbanState {
     }
 }
 
+#[derive(PartialEq, Clone, Copy)]
+pub enum CalendarLayout {
+    Month,
+    Week,
+}
+
+pub struct CalendarState {
+    /// The highlighted day.
+    pub cursor: NaiveDate,
+    pub layout: CalendarLayout,
+    /// Highlighted task among the tasks due on `cursor`.
+    pub task_index: usize,
+    /// Task picked up with `m`, waiting to be dropped on another day.
+    pub moving: Option<String>,
+}
+
+impl Default for CalendarState {
+    fn default() -> Self {
+        Self {
+            cursor: timezone::today(),
+            layout: CalendarLayout::Month,
+            task_index: 0,
+            moving: None,
+        }
+    }
+}
+
 pub struct App<'a> {
     pub db: Database,
     pub tasks: Vec<Task>,
@@ -117,9 +199,33 @@ pub struct App<'a> {
     pub task_form: TaskForm<'a>,
     pub table_state: TableState,
     pub current_view: CurrentView,
-    pub focus_state: FocusState,
+    pub focus_state: FocusTimer,
+    pub focus_config: FocusConfig,
     pub kanban_state: KanbanState,
+    pub calendar: CalendarState,
     pub is_inspecting: bool,
+    pub archive: Vec<Task>,
+    pub archive_state: TableState,
+    pub trash: Vec<Task>,
+    pub trash_state: TableState,
+    pub trash_retention_days: i64,
+    pub checklist_input: TextArea<'a>,
+    pub checklist_index: usize,
+    /// Tasks with at least one unfinished blocker.
+    pub blocked_ids: HashSet<String>,
+    /// Task waiting for its blocker to be picked with `b`.
+    pub link_source: Option<String>,
+    /// Finished tracked seconds per task.
+    pub time_totals: HashMap<String, i64>,
+    pub running_clock: Option<TimeEntry>,
+    /// Completed Pomodoros per task.
+    pub pomodoro_counts: HashMap<String, u32>,
+    /// Linked commits per task, newest first.
+    pub task_commits: HashMap<String, Vec<TaskCommit>>,
+    /// Tasks another task waits on, by id, for the inspector.
+    pub blocking_tasks: HashMap<String, Task>,
+    /// One-shot feedback shown in the status bar until the next key press.
+    pub status_message: Option<String>,
     pub editing_task_id: Option<String>,
     pub search_query: String,
     pub stats: Vec<(String, u64)>,
@@ -128,6 +234,9 @@ pub struct App<'a> {
     pub show_quit_modal: bool,
     pub streak: u32,
     pub tasks_today: u32,
+    /// Active tasks past their due date, for the HUD.
+    pub overdue_count: usize,
+    reminders_checked_at: Option<Instant>,
 }
 
 impl<'a> App<'a> {
@@ -138,6 +247,19 @@ impl<'a> App<'a> {
         let stats = db.get_weekly_stats()?;
         let streak = db.get_streak().unwrap_or(0);
         let tasks_today = db.get_tasks_today().unwrap_or(0);
+        let blocked_ids = db.get_blocked_task_ids()?;
+        let archive = db.get_archived_tasks()?;
+        let trash = db.get_trashed_tasks()?;
+        let trash_retention_days = db.trash_retention_days()?;
+        let time_totals = db.get_time_totals()?;
+        let running_clock = db.running_clock()?;
+        let pomodoro_counts = db.get_pomodoro_counts()?;
+        let task_commits = db.get_task_commits()?;
+        let blocking_tasks = db.get_blocking_tasks()?;
+        let focus_config = db.focus_config()?;
+        let focus_state = db.get_focus_timer()?;
+        let now = Utc::now();
+        let overdue_count = tasks.iter().filter(|t| t.is_overdue(now)).count();
 
         // Load Theme
         let current_theme = if let Ok(Some(theme_str)) = db.get_setting("theme") {
@@ -156,6 +278,14 @@ impl<'a> App<'a> {
         if !tasks.is_empty() {
             table_state.select(Some(0));
         }
+        let mut archive_state = TableState::default();
+        if !archive.is_empty() {
+            archive_state.select(Some(0));
+        }
+        let mut trash_state = TableState::default();
+        if !trash.is_empty() {
+            trash_state.select(Some(0));
+        }
 
         Ok(Self {
             db,
@@ -165,9 +295,26 @@ impl<'a> App<'a> {
             task_form: TaskForm::default(),
             table_state,
             current_view: CurrentView::Splash,
-            focus_state: FocusState::default(),
+            focus_state,
+            focus_config,
             kanban_state: KanbanState::default(),
+            calendar: CalendarState::default(),
             is_inspecting: false,
+            archive,
+            archive_state,
+            trash,
+            trash_state,
+            trash_retention_days,
+            checklist_input: TextArea::default(),
+            checklist_index: 0,
+            blocked_ids,
+            link_source: None,
+            time_totals,
+            running_clock,
+            pomodoro_counts,
+            task_commits,
+            blocking_tasks,
+            status_message: None,
             editing_task_id: None,
             search_query: String::new(),
             stats,
@@ -176,6 +323,8 @@ impl<'a> App<'a> {
             show_quit_modal: false,
             streak,
             tasks_today,
+            overdue_count,
+            reminders_checked_at: None,
         })
     }
 
@@ -192,18 +341,36 @@ impl<'a> App<'a> {
 
     pub fn refresh_state(&mut self) -> Result<()> {
         let all_tasks = self.db.get_all_tasks()?;
+        let archived = self.db.get_archived_tasks()?;
+        self.blocked_ids = self.db.get_blocked_task_ids()?;
+        let now = Utc::now();
+        self.overdue_count = all_tasks.iter().filter(|t| t.is_overdue(now)).count();
 
         if self.search_query.is_empty() {
             self.tasks = all_tasks;
+            self.archive = archived;
         } else {
-            let q = self.search_query.to_lowercase();
+            let query = SearchQuery::parse(&self.search_query);
             self.tasks = all_tasks
                 .into_iter()
-                .filter(|t| {
-                    t.title.to_lowercase().contains(&q) || t.description.to_lowercase().contains(&q)
-                })
+                .filter(|t| query.matches(t, &self.blocked_ids))
+                .collect();
+            self.archive = archived
+                .into_iter()
+                .filter(|t| query.matches(t, &self.blocked_ids))
                 .collect();
         }
+        clamp_selection(&mut self.archive_state, self.archive.len());
+
+        self.trash = self.db.get_trashed_tasks()?;
+        self.trash_retention_days = self.db.trash_retention_days()?;
+        clamp_selection(&mut self.trash_state, self.trash.len());
+
+        self.time_totals = self.db.get_time_totals()?;
+        self.running_clock = self.db.running_clock()?;
+        self.pomodoro_counts = self.db.get_pomodoro_counts()?;
+        self.task_commits = self.db.get_task_commits()?;
+        self.blocking_tasks = self.db.get_blocking_tasks()?;
 
         self.user_profile = self.db.get_user_profile()?;
         self.stats = self.db.get_weekly_stats()?;
@@ -221,40 +388,78 @@ impl<'a> App<'a> {
     }
 
     pub fn save_task(&mut self) -> Result<()> {
-        if self.task_form.title.lines().join("").trim().is_empty() {
-            return Ok(());
-        }
-
-        let title = self.task_form.title.lines().join(" ").trim().to_string();
-        let description = self
-            .task_form
-            .description
-            .lines()
-            .join("\n")
-            .trim()
-            .to_string();
-        let priority = self.task_form.priority;
+        let quick_add = QuickAdd::parse(&self.task_form.title.lines().join(" "));
+        let (description, reward) =
+            extract_reward(self.task_form.description.lines().join("\n").trim());
+        let title = quick_add.title;
+        let priority = quick_add.priority.unwrap_or(self.task_form.priority);
         let xp_str = self.task_form.xp.lines().join("").trim().to_string();
-        let xp_reward = xp_str.parse::<i32>().unwrap_or(10);
-
-        let due_date_str = self.task_form.due_date.lines().join("").trim().to_string();
-        let due_date = if due_date_str.is_empty() {
-            None
-        } else if let Ok(naive) = chrono::NaiveDate::parse_from_str(&due_date_str, "%Y-%m-%d") {
-            Some(DateTime::from_naive_utc_and_offset(
-                naive.and_hms_opt(23, 59, 59).unwrap(),
-                Utc,
-            ))
-        } else {
-            None
+
+        // Keep the form open on a negative reward, or a due date or repeat
+        // rule we can't read, rather than drop it
+        let xp_reward = match reward {
+            Some(xp) => xp,
+            None => match xp_str.parse::<i32>() {
+                Ok(xp) if xp < 0 => {
+                    self.task_form.active_field = FormField::XP;
+                    return Ok(());
+                }
+                Ok(xp) => xp,
+                Err(_) => 10,
+            },
+        };
+        let form_due = match self.task_form.parsed_due_date() {
+            Ok(due) => due,
+            Err(_) => {
+                self.task_form.active_field = FormField::DueDate;
+                return Ok(());
+            }
+        };
+        let due_date = quick_add.due_date.or(form_due);
+        let recurrence = match self.task_form.parsed_recurrence() {
+            Ok(recurrence) => recurrence,
+            Err(_) => {
+                self.task_form.active_field = FormField::Repeat;
+                return Ok(());
+            }
         };
 
+        let form_project = self.task_form.project.lines().join("").trim().to_string();
+        let project = quick_add
+            .project
+            .or_else(|| (!form_project.is_empty()).then_some(form_project));
+        let mut tags = parse_tags(&self.task_form.tags.lines().join(" "));
+        for tag in quick_add.tags {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        self.input_mode = InputMode::Normal;
+        if title.is_empty() {
+            return Ok(());
+        }
+
         if let Some(id) = &self.editing_task_id {
-            self.db
-                .update_task_content(id, &title, &description, priority, due_date)?;
+            self.db.record(&format!("Edit '{}'", title), |db| {
+                db.update_task_content(
+                    id,
+                    &title,
+                    &description,
+                    priority,
+                    xp_reward,
+                    due_date,
+                    recurrence.as_ref(),
+                )?;
+                db.set_task_labels(id, project.as_deref(), &tags)
+            })?;
         } else {
-            let task = Task::new(title, description, priority, xp_reward, due_date);
-            self.db.create_task(&task)?;
+            let mut task = Task::new(title, description, priority, xp_reward, due_date);
+            task.recurrence = recurrence;
+            task.project = project;
+            task.tags = tags;
+            self.db
+                .record(&format!("Add '{}'", task.title), |db| db.create_task(&task))?;
         }
 
         self.editing_task_id = None;
@@ -263,6 +468,18 @@ impl<'a> App<'a> {
         Ok(())
     }
 
+    /// Opens an empty task form; from the Calendar the due date is the
+    /// highlighted day.
+    pub fn start_new_task(&mut self) {
+        self.editing_task_id = None;
+        self.task_form = TaskForm::default();
+        if self.current_view == CurrentView::Calendar {
+            let day = self.calendar.cursor.format("%Y-%m-%d").to_string();
+            self.task_form.due_date = TextArea::new(vec![day]);
+        }
+        self.input_mode = InputMode::Editing;
+    }
+
     pub fn start_editing(&mut self) {
         if self.current_view != CurrentView::Dashboard {
             return;
@@ -280,10 +497,20 @@ impl<'a> App<'a> {
 
                 let due_str = task
                     .due_date
-                    .map(|d| d.format("%Y-%m-%d").to_string())
+                    .map(|d| format_due(d, "%Y-%m-%d"))
                     .unwrap_or_default();
                 self.task_form.due_date = TextArea::new(vec![due_str]);
 
+                let repeat_str = task
+                    .recurrence
+                    .as_ref()
+                    .map(|r| r.to_string())
+                    .unwrap_or_default();
+                self.task_form.recurrence = TextArea::new(vec![repeat_str]);
+                self.task_form.project =
+                    TextArea::new(vec![task.project.clone().unwrap_or_default()]);
+                self.task_form.tags = TextArea::new(vec![task.tags.join(", ")]);
+
                 self.task_form.active_field = FormField::Title;
                 self.input_mode = InputMode::Editing;
             }
@@ -293,14 +520,113 @@ impl<'a> App<'a> {
     pub fn toggle_inspector(&mut self) {
         if self.current_view == CurrentView::Dashboard && !self.tasks.is_empty() {
             self.is_inspecting = !self.is_inspecting;
+            self.checklist_index = 0;
+        }
+    }
+
+    fn selected_task(&self) -> Option<&Task> {
+        self.table_state.selected().and_then(|i| self.tasks.get(i))
+    }
+
+    pub fn start_checklist_input(&mut self) {
+        if self.current_view != CurrentView::Dashboard || self.selected_task().is_none() {
+            return;
+        }
+        self.checklist_input = TextArea::default();
+        self.checklist_input
+            .set_placeholder_text("Checklist item...");
+        self.input_mode = InputMode::Checklist;
+    }
+
+    pub fn save_checklist_item(&mut self) -> Result<()> {
+        let title = self.checklist_input.lines().join(" ").trim().to_string();
+        if let Some(task) = self.selected_task() {
+            if !title.is_empty() {
+                let id = task.id.clone();
+                self.db.record(&format!("Add item '{}'", title), |db| {
+                    db.add_subtask(&id, &title)
+                })?;
+                self.refresh_state()?;
+            }
         }
+        self.input_mode = InputMode::Normal;
+        Ok(())
+    }
+
+    pub fn next_checklist_item(&mut self) {
+        let count = self.selected_task().map_or(0, |t| t.subtasks.len());
+        if count > 0 {
+            self.checklist_index = (self.checklist_index + 1) % count;
+        }
+    }
+
+    pub fn previous_checklist_item(&mut self) {
+        let count = self.selected_task().map_or(0, |t| t.subtasks.len());
+        if count > 0 {
+            self.checklist_index = (self.checklist_index + count - 1) % count;
+        }
+    }
+
+    /// Ticks the highlighted checklist item. Ticking the last open item
+    /// completes the parent task unless `checklist_auto_complete` is off.
+    pub fn toggle_checklist_item(&mut self) -> Result<()> {
+        let Some(task) = self.selected_task() else {
+            return Ok(());
+        };
+        let Some(subtask) = task.subtasks.get(self.checklist_index) else {
+            return Ok(());
+        };
+
+        let all_done = task.subtasks.iter().enumerate().all(|(i, s)| {
+            if i == self.checklist_index {
+                !s.done
+            } else {
+                s.done
+            }
+        });
+        let complete_parent = all_done
+            && task.status != TaskStatus::Done
+            && self.db.get_setting("checklist_auto_complete")?.as_deref() != Some("false");
+        let (subtask_id, done) = (subtask.id, !subtask.done);
+        let label = format!("Tick '{}'", subtask.title);
+        let task = task.clone();
+
+        let completed = self.db.record(&label, |db| {
+            db.set_subtask_done(subtask_id, done)?;
+            Ok(complete_parent && db.complete_task(&task)?)
+        })?;
+        // A blocked parent stays open, but the item still gets ticked
+        if complete_parent && !completed {
+            self.status_message = Some(self.blocked_message(&task));
+        }
+        self.refresh_state()
+    }
+
+    pub fn delete_checklist_item(&mut self) -> Result<()> {
+        let Some(subtask) = self
+            .selected_task()
+            .and_then(|t| t.subtasks.get(self.checklist_index))
+        else {
+            return Ok(());
+        };
+        let (subtask_id, label) = (subtask.id, format!("Remove item '{}'", subtask.title));
+        self.db.record(&label, |db| db.delete_subtask(subtask_id))?;
+        self.checklist_index = self.checklist_index.saturating_sub(1);
+        self.refresh_state()
     }
 
     pub fn next_item(&mut self) {
         match self.current_view {
             CurrentView::Dashboard => self.next_dashboard_task(),
             CurrentView::Kanban => self.next_kanban_item(),
-            CurrentView::Focus | CurrentView::Analytics | CurrentView::Splash => {}
+            CurrentView::Archive => {
+                step_selection(&mut self.archive_state, self.archive.len(), true)
+            }
+            CurrentView::Trash => step_selection(&mut self.trash_state, self.trash.len(), true),
+            CurrentView::Calendar
+            | CurrentView::Focus
+            | CurrentView::Analytics
+            | CurrentView::Splash => {}
         }
     }
 
@@ -308,7 +634,14 @@ impl<'a> App<'a> {
         match self.current_view {
             CurrentView::Dashboard => self.previous_dashboard_task(),
             CurrentView::Kanban => self.previous_kanban_item(),
-            CurrentView::Focus | CurrentView::Analytics | CurrentView::Splash => {}
+            CurrentView::Archive => {
+                step_selection(&mut self.archive_state, self.archive.len(), false)
+            }
+            CurrentView::Trash => step_selection(&mut self.trash_state, self.trash.len(), false),
+            CurrentView::Calendar
+            | CurrentView::Focus
+            | CurrentView::Analytics
+            | CurrentView::Splash => {}
         }
     }
 
@@ -353,12 +686,7 @@ impl<'a> App<'a> {
             return;
         }
 
-        let state = match self.kanban_state.focused_col {
-            0 => &mut self.kanban_state.todo_state,
-            1 => &mut self.kanban_state.doing_state,
-            2 => &mut self.kanban_state.done_state,
-            _ => return,
-        };
+        let state = self.kanban_state.col_state(self.kanban_state.focused_col);
 
         let i = match state.selected() {
             Some(i) => {
@@ -380,12 +708,7 @@ impl<'a> App<'a> {
             return;
         }
 
-        let state = match self.kanban_state.focused_col {
-            0 => &mut self.kanban_state.todo_state,
-            1 => &mut self.kanban_state.doing_state,
-            2 => &mut self.kanban_state.done_state,
-            _ => return,
-        };
+        let state = self.kanban_state.col_state(self.kanban_state.focused_col);
 
         let i = match state.selected() {
             Some(i) => {
@@ -416,6 +739,113 @@ impl<'a> App<'a> {
         }
     }
 
+    /// The visible tasks of one Kanban column, in their manual order.
+    pub fn kanban_tasks(&self, status: &TaskStatus) -> Vec<&Task> {
+        let mut tasks: Vec<&Task> = self.tasks.iter().filter(|t| &t.status == status).collect();
+        tasks.sort_by_key(|t| t.position);
+        tasks
+    }
+
+    fn selected_kanban_task(&mut self) -> Option<Task> {
+        let col = self.kanban_state.focused_col;
+        let i = self.kanban_state.col_state(col).selected()?;
+        let status = self.get_status_from_col(col);
+        self.kanban_tasks(&status).get(i).map(|t| (*t).clone())
+    }
+
+    /// `H`/`L`: moves the selected card to the top of the neighbouring column.
+    pub fn move_kanban_task(&mut self, forward: bool) -> Result<()> {
+        if self.current_view != CurrentView::Kanban {
+            return Ok(());
+        }
+        let col = self.kanban_state.focused_col;
+        let target = match (forward, col) {
+            (true, 0 | 1) => col + 1,
+            (false, 1 | 2) => col - 1,
+            _ => return Ok(()),
+        };
+        let Some(task) = self.selected_kanban_task() else {
+            return Ok(());
+        };
+        let new_status = self.get_status_from_col(target);
+
+        if new_status != TaskStatus::Todo && self.blocked_ids.contains(&task.id) {
+            self.status_message = Some(self.blocked_message(&task));
+            return Ok(());
+        }
+
+        let mut order = vec![task.id.clone()];
+        order.extend(
+            self.column_order(&new_status)?
+                .into_iter()
+                .filter(|id| *id != task.id),
+        );
+        let label = format!("Move '{}' to {}", task.title, new_status);
+        self.db.record(&label, |db| {
+            if new_status == TaskStatus::Done {
+                db.complete_task(&task)?;
+            } else {
+                db.update_task_status(&task.id, new_status.clone())?;
+            }
+            db.set_positions(&order)
+        })?;
+        self.status_message = self.start_branch(&task, &new_status);
+        self.refresh_state()?;
+
+        let remaining = self.kanban_tasks(&task.status).len();
+        let source = self.kanban_state.col_state(col);
+        if source.selected().is_some_and(|i| i >= remaining) {
+            source.select(Some(remaining.saturating_sub(1)));
+        }
+        self.kanban_state.focused_col = target;
+        self.kanban_state.col_state(target).select(Some(0));
+        Ok(())
+    }
+
+    /// `J`/`K`: swaps the selected card with its visible neighbour.
+    pub fn reorder_kanban_task(&mut self, down: bool) -> Result<()> {
+        if self.current_view != CurrentView::Kanban {
+            return Ok(());
+        }
+        let col = self.kanban_state.focused_col;
+        let status = self.get_status_from_col(col);
+        let Some(i) = self.kanban_state.col_state(col).selected() else {
+            return Ok(());
+        };
+        let j = if down { i + 1 } else { i.wrapping_sub(1) };
+        let column = self.kanban_tasks(&status);
+        let (Some(a), Some(b)) = (column.get(i), column.get(j)) else {
+            return Ok(());
+        };
+        let label = format!("Reorder '{}'", a.title);
+        let (a, b) = (a.id.clone(), b.id.clone());
+
+        // Reorder against the full column so tasks hidden by a search keep their place.
+        let mut order = self.column_order(&status)?;
+        if let (Some(x), Some(y)) = (
+            order.iter().position(|id| *id == a),
+            order.iter().position(|id| *id == b),
+        ) {
+            order.swap(x, y);
+        }
+        self.db.record(&label, |db| db.set_positions(&order))?;
+        self.refresh_state()?;
+        self.kanban_state.col_state(col).select(Some(j));
+        Ok(())
+    }
+
+    /// Ids of every task with `status`, ignoring the search filter.
+    fn column_order(&self, status: &TaskStatus) -> Result<Vec<String>> {
+        let mut tasks: Vec<Task> = self
+            .db
+            .get_all_tasks()?
+            .into_iter()
+            .filter(|t| &t.status == status)
+            .collect();
+        tasks.sort_by_key(|t| t.position);
+        Ok(tasks.into_iter().map(|t| t.id).collect())
+    }
+
     fn get_status_from_col(&self, col: usize) -> TaskStatus {
         match col {
             0 => TaskStatus::Todo,
@@ -425,14 +855,114 @@ impl<'a> App<'a> {
         }
     }
 
+    /// Visible tasks due on `day` (in the local timezone), earliest first.
+    pub fn calendar_tasks(&self, day: NaiveDate) -> Vec<&Task> {
+        let mut tasks: Vec<&Task> = self
+            .tasks
+            .iter()
+            .filter(|t| t.due_date.is_some_and(|d| timezone::due_parts(d).0 == day))
+            .collect();
+        tasks.sort_by_key(|t| t.due_date);
+        tasks
+    }
+
+    fn selected_calendar_task(&self) -> Option<&Task> {
+        self.calendar_tasks(self.calendar.cursor)
+            .get(self.calendar.task_index)
+            .copied()
+    }
+
+    /// `h`/`l` move a day, `j`/`k` a week.
+    pub fn move_calendar_cursor(&mut self, days: i64) {
+        self.calendar.cursor += Duration::days(days);
+        self.calendar.task_index = 0;
+    }
+
+    /// `[`/`]`: the previous or next month (or week in the week layout).
+    pub fn page_calendar(&mut self, forward: bool) {
+        let cursor = self.calendar.cursor;
+        self.calendar.cursor = match (self.calendar.layout, forward) {
+            (CalendarLayout::Month, true) => cursor + Months::new(1),
+            (CalendarLayout::Month, false) => cursor - Months::new(1),
+            (CalendarLayout::Week, true) => cursor + Duration::days(7),
+            (CalendarLayout::Week, false) => cursor - Duration::days(7),
+        };
+        self.calendar.task_index = 0;
+    }
+
+    pub fn calendar_today(&mut self) {
+        self.calendar.cursor = timezone::today();
+        self.calendar.task_index = 0;
+    }
+
+    pub fn toggle_calendar_layout(&mut self) {
+        self.calendar.layout = match self.calendar.layout {
+            CalendarLayout::Month => CalendarLayout::Week,
+            CalendarLayout::Week => CalendarLayout::Month,
+        };
+    }
+
+    /// `J`/`K`: the next or previous task on the highlighted day.
+    pub fn step_calendar_task(&mut self, forward: bool) {
+        let count = self.calendar_tasks(self.calendar.cursor).len();
+        if count > 0 {
+            let i = self.calendar.task_index;
+            self.calendar.task_index = if forward {
+                (i + 1) % count
+            } else {
+                (i + count - 1) % count
+            };
+        }
+    }
+
+    /// `m` picks up the highlighted task; `m` on another day drops it there,
+    /// keeping its time of day.
+    pub fn move_calendar_task(&mut self) -> Result<()> {
+        if self.current_view != CurrentView::Calendar {
+            return Ok(());
+        }
+        let Some(id) = self.calendar.moving.take() else {
+            self.calendar.moving = self.selected_calendar_task().map(|t| t.id.clone());
+            return Ok(());
+        };
+        let Some(task) = self.tasks.iter().find(|t| t.id == id) else {
+            return Ok(());
+        };
+        let Some((day, time)) = task.due_date.map(timezone::due_parts) else {
+            return Ok(());
+        };
+        let target = self.calendar.cursor;
+        if day == target {
+            return Ok(());
+        }
+
+        let due = timezone::to_utc(target.and_time(time));
+        let label = format!("Reschedule '{}' to {}", task.title, target.format("%b %d"));
+        self.db
+            .record(&label, |db| db.set_due_date(&id, Some(due)))?;
+        self.refresh_state()?;
+        self.calendar.task_index = self
+            .calendar_tasks(target)
+            .iter()
+            .position(|t| t.id == id)
+            .unwrap_or(0);
+        self.status_message = Some(label);
+        Ok(())
+    }
+
     pub fn cycle_view(&mut self) {
         self.current_view = match self.current_view {
             CurrentView::Splash => CurrentView::Dashboard,
             CurrentView::Dashboard => CurrentView::Kanban,
-            CurrentView::Kanban => CurrentView::Focus,
+            CurrentView::Kanban => CurrentView::Calendar,
+            CurrentView::Calendar => CurrentView::Focus,
             CurrentView::Focus => CurrentView::Analytics,
-            CurrentView::Analytics => CurrentView::Dashboard,
+            CurrentView::Analytics => CurrentView::Archive,
+            CurrentView::Archive => CurrentView::Trash,
+            CurrentView::Trash => CurrentView::Dashboard,
         };
+        // A move in progress doesn't carry over to other views
+        self.calendar.moving = None;
     }
 
     pub fn toggle_status(&mut self) -> Result<()> {
@@ -448,12 +978,23 @@ impl<'a> App<'a> {
                     TaskStatus::Done => TaskStatus::Todo,
                 };
 
-                if new_status == TaskStatus::Done && task.status != TaskStatus::Done {
-                    self.db.add_xp(task.xp_reward)?;
+                if new_status != TaskStatus::Todo && self.blocked_ids.contains(&task.id) {
+                    self.status_message = Some(self.blocked_message(task));
+                    return Ok(());
                 }
 
-                self.db.update_task_status(&task.id, new_status)?;
+                let label = format!("Mark '{}' {}", task.title, new_status);
+                self.db.record(&label, |db| {
+                    if new_status == TaskStatus::Done {
+                        db.complete_task(task)?;
+                        Ok(())
+                    } else {
+                        db.update_task_status(&task.id, new_status.clone())
+                    }
+                })?;
+                let branch_message = self.start_branch(task, &new_status);
                 self.refresh_state()?;
+                self.status_message = branch_message;
                 if i < self.tasks.len() {
                     self.table_state.select(Some(i));
                 }
@@ -462,6 +1003,72 @@ impl<'a> App<'a> {
         Ok(())
     }
 
+    /// With `git_branch_on_start` on, starting a task switches the repository
+    /// Zenith was launched in to the task's branch.
+    fn start_branch(&self, task: &Task, new_status: &TaskStatus) -> Option<String> {
+        if *new_status != TaskStatus::Doing
+            || task.status == TaskStatus::Doing
+            || self.db.get_setting("git_branch_on_start").ok()?.as_deref() != Some("true")
+        {
+            return None;
+        }
+        Some(match git::switch_to_branch(task) {
+            Ok(name) => format!("Switched to branch {}", name),
+            Err(e) => format!("No branch: {}", e),
+        })
+    }
+
+    fn blocked_message(&self, task: &Task) -> String {
+        let names: Vec<String> = self
+            .db
+            .get_all_tasks()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|t| task.blocked_by.contains(&t.id) && t.status != TaskStatus::Done)
+            .map(|t| t.title)
+            .collect();
+        format!("🔒 Blocked by: {}", names.join(", "))
+    }
+
+    /// First `b` marks the selected task as blocked; the second `b` (on
+    /// another task) picks what it's blocked by.
+    pub fn link_dependency(&mut self) -> Result<()> {
+        if self.current_view != CurrentView::Dashboard {
+            return Ok(());
+        }
+        let Some(selected) = self.selected_task() else {
+            return Ok(());
+        };
+        let selected_id = selected.id.clone();
+
+        match self.link_source.take() {
+            None => self.link_source = Some(selected_id),
+            Some(task_id) if task_id == selected_id => {}
+            Some(task_id) => {
+                let already = self
+                    .tasks
+                    .iter()
+                    .any(|t| t.id == task_id && t.blocked_by.contains(&selected_id));
+                let result = if already {
+                    self.db.record("Remove dependency", |db| {
+                        db.remove_dependency(&task_id, &selected_id)
+                    })
+                } else {
+                    self.db.record("Add dependency", |db| {
+                        db.add_dependency(&task_id, &selected_id)
+                    })
+                };
+                self.status_message = Some(match result {
+                    Ok(()) if already => "Dependency removed".to_string(),
+                    Ok(()) => "Dependency added".to_string(),
+                    Err(e) => e.to_string(),
+                });
+                self.refresh_state()?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn delete_current_task(&mut self) -> Result<()> {
         if self.current_view != CurrentView::Dashboard {
             return Ok(());
@@ -469,7 +1076,10 @@ impl<'a> App<'a> {
 
         if let Some(i) = self.table_state.selected() {
             if let Some(task) = self.tasks.get(i) {
-                self.db.delete_task(&task.id)?;
+                self.db.record(&format!("Delete '{}'", task.title), |db| {
+                    db.delete_task(&task.id)
+                })?;
+                self.status_message = Some(format!("Moved '{}' to the Trash", task.title));
                 self.refresh_state()?;
 
                 if self.tasks.is_empty() {
@@ -484,44 +1094,277 @@ impl<'a> App<'a> {
         Ok(())
     }
 
-    pub fn toggle_timer(&mut self) {
-        self.focus_state.is_running = !self.focus_state.is_running;
-        if self.focus_state.is_running {
-            self.focus_state.last_tick = Some(Utc::now());
+    /// `a` on the dashboard archives a finished task; in the Archive view it
+    /// brings the task back to the active list.
+    pub fn toggle_archive(&mut self) -> Result<()> {
+        match self.current_view {
+            CurrentView::Dashboard => {
+                let Some(task) = self.selected_task() else {
+                    return Ok(());
+                };
+                if task.status != TaskStatus::Done {
+                    self.status_message = Some("Only finished tasks can be archived".to_string());
+                    return Ok(());
+                }
+                let (id, title) = (task.id.clone(), task.title.clone());
+                self.db
+                    .record(&format!("Archive '{}'", title), |db| db.archive_task(&id))?;
+                self.status_message = Some(format!("Archived: {}", title));
+            }
+            CurrentView::Archive => {
+                let Some(task) = self
+                    .archive_state
+                    .selected()
+                    .and_then(|i| self.archive.get(i))
+                else {
+                    return Ok(());
+                };
+                let (id, title) = (task.id.clone(), task.title.clone());
+                self.db.record(&format!("Unarchive '{}'", title), |db| {
+                    db.unarchive_task(&id)
+                })?;
+                self.status_message = Some(format!("Back in the task list: {}", title));
+            }
+            _ => return Ok(()),
+        }
+        self.refresh_after_history()
+    }
+
+    fn selected_trash(&self) -> Option<&Task> {
+        self.trash_state.selected().and_then(|i| self.trash.get(i))
+    }
+
+    pub fn restore_trashed_task(&mut self) -> Result<()> {
+        let Some(task) = self.selected_trash() else {
+            return Ok(());
+        };
+        let (id, label) = (task.id.clone(), format!("Restore '{}'", task.title));
+        self.db.record(&label, |db| db.restore_task(&id))?;
+        self.status_message = Some(format!("Restored: {}", task.title));
+        self.refresh_state()
+    }
+
+    /// Permanently deletes the selected Trash entry. This can't be undone.
+    pub fn purge_trashed_task(&mut self) -> Result<()> {
+        let Some(task) = self.selected_trash() else {
+            return Ok(());
+        };
+        let id = task.id.clone();
+        self.status_message = Some(format!("Purged: {}", task.title));
+        self.db.erase_task(&id)?;
+        self.refresh_state()
+    }
+
+    pub fn undo(&mut self) -> Result<()> {
+        self.status_message = Some(match self.db.undo()? {
+            Some(label) => format!("Undid: {}", label),
+            None => "Nothing to undo".to_string(),
+        });
+        self.refresh_after_history()
+    }
+
+    pub fn redo(&mut self) -> Result<()> {
+        self.status_message = Some(match self.db.redo()? {
+            Some(label) => format!("Redid: {}", label),
+            None => "Nothing to redo".to_string(),
+        });
+        self.refresh_after_history()
+    }
+
+    fn refresh_after_history(&mut self) -> Result<()> {
+        self.refresh_state()?;
+        clamp_selection(&mut self.table_state, self.tasks.len());
+        Ok(())
+    }
+
+    /// Total tracked seconds for a task, including the running clock.
+    pub fn tracked_seconds(&self, task_id: &str) -> i64 {
+        let running = self
+            .running_clock
+            .as_ref()
+            .filter(|c| c.task_id == task_id)
+            .map_or(0, |c| c.seconds());
+        self.time_totals.get(task_id).copied().unwrap_or(0) + running
+    }
+
+    pub fn is_clocked_in(&self, task_id: &str) -> bool {
+        self.running_clock
+            .as_ref()
+            .is_some_and(|c| c.task_id == task_id)
+    }
+
+    /// `c`: clocks in on the selected task, or out if it's the one running.
+    pub fn toggle_clock(&mut self) -> Result<()> {
+        if self.current_view != CurrentView::Dashboard {
+            return Ok(());
+        }
+        let Some(task) = self.selected_task() else {
+            return Ok(());
+        };
+        let (id, title) = (task.id.clone(), task.title.clone());
+
+        if self.is_clocked_in(&id) {
+            let spent = self.running_clock.as_ref().map_or(0, |c| c.seconds());
+            self.db.stop_clock()?;
+            self.status_message = Some(format!(
+                "Clocked out: {} ({})",
+                title,
+                format_duration(spent)
+            ));
         } else {
-            self.focus_state.last_tick = None;
+            self.db.start_clock(&id)?;
+            self.status_message = Some(format!("Clocked in: {}", title));
         }
+        self.refresh_state()
     }
 
-    pub fn reset_timer(&mut self) {
-        self.focus_state.is_running = false;
-        self.focus_state.remaining_sec = self.focus_state.duration_sec;
-        self.focus_state.last_tick = None;
+    /// Silences the selected task's reminders for the `snooze` setting.
+    pub fn snooze_selected(&mut self) -> Result<()> {
+        if self.current_view != CurrentView::Dashboard {
+            return Ok(());
+        }
+        let Some(task) = self.selected_task() else {
+            return Ok(());
+        };
+        if task.due_date.is_none() {
+            self.status_message = Some(format!("{} has no due date", task.title));
+            return Ok(());
+        }
+        let (id, title) = (task.id.clone(), task.title.clone());
+        let Some(until) = Utc::now().checked_add_signed(self.db.snooze_length()?) else {
+            return Ok(());
+        };
+        self.db.snooze_task(&id, until)?;
+        self.status_message = Some(format!(
+            "Snoozed {} until {}",
+            title,
+            format_local(until, "%H:%M")
+        ));
+        Ok(())
     }
 
-    pub fn on_tick(&mut self) {
-        if self.focus_state.is_running {
-            let now = Utc::now();
-            if let Some(last_tick) = self.focus_state.last_tick {
-                let delta = now.signed_duration_since(last_tick).num_seconds();
-                if delta > 0 {
-                    if self.focus_state.remaining_sec >= delta {
-                        self.focus_state.remaining_sec -= delta;
-                    } else {
-                        self.focus_state.remaining_sec = 0;
-                        self.focus_state.is_running = false;
-
-                        // Notify
-                        let _ = Notification::new()
-                            .summary("Zenith Focus")
-                            .body("Session Complete! Take a break.")
-                            .show();
-                    }
-                    self.focus_state.last_tick = Some(now);
-                }
-            } else {
-                self.focus_state.last_tick = Some(now);
-            }
+    /// Sends the reminders that have come due and refreshes the overdue
+    /// count, which changes with the clock rather than the data.
+    fn check_reminders(&mut self) -> Result<()> {
+        let now = Utc::now();
+        let reminders = self.db.take_due_reminders(now)?;
+        for reminder in &reminders {
+            notify_reminder(reminder);
         }
+        if let Some(last) = reminders.last() {
+            self.status_message = Some(format!("⏰ {}: {}", last.task.title, reminder_text(last)));
+        }
+        self.overdue_count = self
+            .db
+            .get_all_tasks()?
+            .iter()
+            .filter(|t| t.is_overdue(now))
+            .count();
+        Ok(())
+    }
+
+    /// Starting a session clocks in on the selected task; pausing clocks out.
+    /// The timer lives in the database, so a notifier is started to finish
+    /// the phase if the TUI is closed first.
+    pub fn toggle_timer(&mut self) -> Result<()> {
+        if self.focus_state.is_running() {
+            self.db.pause_focus()?;
+        } else {
+            let task_id = self.selected_task().map(|t| t.id.clone());
+            self.db.start_focus(task_id.as_deref())?;
+            spawn_notifier(&self.db)?;
+        }
+        self.refresh_focus()
+    }
+
+    /// Restarts the current phase from its configured length.
+    pub fn reset_timer(&mut self) -> Result<()> {
+        self.db.reset_focus()?;
+        self.refresh_focus()
+    }
+
+    /// `s`: ends the current phase early. A skipped work phase is recorded as
+    /// aborted; the timer keeps running if it was.
+    pub fn skip_phase(&mut self) -> Result<()> {
+        if self.current_view != CurrentView::Focus {
+            return Ok(());
+        }
+        let next = self.db.skip_focus()?;
+        self.status_message = Some(format!("Skipped to {}", next.label().to_lowercase()));
+        self.refresh_focus()
+    }
+
+    /// `+`: adds `FOCUS_EXTEND_SEC` to the current phase.
+    pub fn extend_phase(&mut self) -> Result<()> {
+        if self.current_view != CurrentView::Focus {
+            return Ok(());
+        }
+        self.db.extend_focus(FOCUS_EXTEND_SEC)?;
+        self.status_message = Some(format!(
+            "{} extended by {} min",
+            self.focus_state.phase.label().to_lowercase(),
+            FOCUS_EXTEND_SEC / 60
+        ));
+        self.refresh_focus()
+    }
+
+    /// Reloads the timer and whatever a phase change touched (XP, clocks,
+    /// Pomodoro counts).
+    fn refresh_focus(&mut self) -> Result<()> {
+        self.focus_config = self.db.focus_config()?;
+        self.focus_state = self.db.get_focus_timer()?;
+        self.refresh_state()
+    }
+
+    /// Finishes the phase once it reaches zero, unless the notifier already
+    /// did, and picks up timer changes made from the CLI.
+    pub fn on_tick(&mut self) -> Result<()> {
+        if self
+            .reminders_checked_at
+            .is_none_or(|at| at.elapsed() >= REMINDER_CHECK_INTERVAL)
+        {
+            self.reminders_checked_at = Some(Instant::now());
+            self.check_reminders()?;
+        }
+
+        if let Some(finished) = self.db.tick_focus()? {
+            self.refresh_focus()?;
+            notify_phase_end(finished, &self.focus_state);
+            return Ok(());
+        }
+
+        let timer = self.db.get_focus_timer()?;
+        let moved = timer.phase != self.focus_state.phase
+            || timer.round != self.focus_state.round
+            || timer.is_running() != self.focus_state.is_running();
+        self.focus_state = timer;
+        if moved {
+            self.refresh_state()?;
+        }
+        Ok(())
+    }
+}
+
+/// Keeps a table selection inside `len` rows, selecting the first row when
+/// there is something to select.
+fn clamp_selection(state: &mut TableState, len: usize) {
+    match state.selected() {
+        _ if len == 0 => state.select(None),
+        Some(i) if i >= len => state.select(Some(len - 1)),
+        None => state.select(Some(0)),
+        _ => {}
+    }
+}
+
+/// Moves a table selection one row, wrapping at either end.
+fn step_selection(state: &mut TableState, len: usize, forward: bool) {
+    if len == 0 {
+        return;
     }
+    let i = match state.selected() {
+        Some(i) if forward => (i + 1) % len,
+        Some(i) => (i + len - 1) % len,
+        None => 0,
+    };
+    state.select(Some(i));
 }
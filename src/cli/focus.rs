@@ -0,0 +1,115 @@
+use super::{resolve_task, FocusAction};
+use crate::db::focus::FOCUS_BONUS_XP;
+use crate::db::models::{FocusPhase, FocusTimer};
+use crate::db::Database;
+use color_eyre::eyre::Result;
+use notify_rust::Notification;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// How often the notifier wakes up to refresh its heartbeat.
+const NOTIFIER_INTERVAL_SEC: i64 = 5;
+
+pub fn run(db: &Database, action: FocusAction) -> Result<()> {
+    match action {
+        FocusAction::Start { id } => {
+            let task = id.map(|id| resolve_task(db, &id)).transpose()?;
+            db.start_focus(task.as_ref().map(|t| t.id.as_str()))?;
+            spawn_notifier(db)?;
+            print_status(db)?;
+        }
+        FocusAction::Pause => {
+            db.pause_focus()?;
+            print_status(db)?;
+        }
+        FocusAction::Status => print_status(db)?,
+        FocusAction::Notifier => run_notifier(db)?,
+    }
+    Ok(())
+}
+
+fn print_status(db: &Database) -> Result<()> {
+    db.tick_focus()?;
+    let timer = db.get_focus_timer()?;
+    let config = db.focus_config()?;
+    let remaining = timer.remaining();
+    println!(
+        "{} • round {}/{} • {:02}:{:02} left ({})",
+        timer.phase.label(),
+        timer.round,
+        config.long_break_every,
+        remaining / 60,
+        remaining % 60,
+        if timer.is_running() {
+            "running"
+        } else {
+            "paused"
+        }
+    );
+    if let Some(id) = &timer.session_task {
+        if let Some(task) = db.get_every_task()?.into_iter().find(|t| &t.id == id) {
+            println!("Task: {}", task.title);
+        }
+    }
+    Ok(())
+}
+
+/// Starts `zenith-cli focus notifier` in the background unless one is
+/// already running.
+pub fn spawn_notifier(db: &Database) -> Result<()> {
+    if db.focus_notifier_alive()? {
+        return Ok(());
+    }
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .args(["focus", "notifier"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Its own process group, so closing the shell doesn't take it down
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    command.spawn()?;
+    Ok(())
+}
+
+/// Sleeps until the running phase is due, finishes it and notifies, then
+/// follows the next phase. Exits once the timer is paused.
+fn run_notifier(db: &Database) -> Result<()> {
+    loop {
+        db.touch_focus_notifier(true)?;
+        if let Some(finished) = db.tick_focus()? {
+            notify_phase_end(finished, &db.get_focus_timer()?);
+        }
+
+        let timer = db.get_focus_timer()?;
+        if !timer.is_running() {
+            db.touch_focus_notifier(false)?;
+            // Started again while we were signing off
+            if !db.get_focus_timer()?.is_running() {
+                return Ok(());
+            }
+            continue;
+        }
+        let wait = timer.remaining().clamp(1, NOTIFIER_INTERVAL_SEC);
+        thread::sleep(Duration::from_secs(wait as u64));
+    }
+}
+
+/// Desktop notification for the end of `finished`; `next` is the phase that
+/// has just started.
+pub fn notify_phase_end(finished: FocusPhase, next: &FocusTimer) {
+    let body = match finished {
+        FocusPhase::Work => format!(
+            "Session Complete! +{} XP. Time for a {}.",
+            FOCUS_BONUS_XP,
+            next.phase.label().to_lowercase()
+        ),
+        _ => format!("Break's over. Round {} starts now.", next.round),
+    };
+    let _ = Notification::new()
+        .summary("Zenith Focus")
+        .body(&body)
+        .show();
+}
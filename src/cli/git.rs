@@ -0,0 +1,103 @@
+use super::{resolve_task, GitAction, HookAction};
+use crate::db::models::{Task, TaskStatus};
+use crate::db::Database;
+use crate::interop::git;
+use crate::utils::short_id;
+use color_eyre::eyre::{eyre, Result};
+use std::fs;
+use std::path::Path;
+
+pub fn run(db: &Database, action: GitAction) -> Result<()> {
+    match action {
+        GitAction::Hook {
+            action: HookAction::Install { force },
+        } => {
+            for path in git::install_hooks(force)? {
+                println!("Installed {}", path.display());
+            }
+            println!("Commits mentioning zen:<id> are linked to the task; closes zen:<id> also completes it");
+        }
+        GitAction::Hook {
+            action: HookAction::Uninstall,
+        } => {
+            let removed = git::uninstall_hooks()?;
+            if removed.is_empty() {
+                println!("No zenith-cli hooks installed");
+            }
+            for path in removed {
+                println!("Removed {}", path.display());
+            }
+        }
+        GitAction::CommitMsg { .. } => unreachable!("handled before the database is opened"),
+        GitAction::PostCommit => link_head(db)?,
+    }
+    Ok(())
+}
+
+/// Catches a mistyped `zen:<id>` while the message can still be fixed. A
+/// message without mentions never touches the database, and one with them
+/// only reads it: a database that is missing or on another schema version
+/// skips the check rather than block the commit.
+pub fn check_commit_msg(file: &Path) -> Result<()> {
+    let refs = git::task_refs(&fs::read_to_string(file)?);
+    if refs.is_empty() {
+        return Ok(());
+    }
+    let Some(db) = Database::open_existing_read_only()? else {
+        return Ok(());
+    };
+    for task_ref in refs {
+        resolve_task(&db, &task_ref.prefix).map_err(|e| {
+            eyre!(
+                "zen:{}: {} (commit with --no-verify to skip this check)",
+                task_ref.prefix,
+                e
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Links `HEAD` to the tasks its message mentions, and to the task whose
+/// branch it was made on, completing those it closes.
+fn link_head(db: &Database) -> Result<()> {
+    let (commit, message) = git::head_commit()?;
+    let mut mentions = git::task_refs(&message);
+    if let Some(prefix) = git::branch_task_prefix() {
+        if !mentions.iter().any(|r| r.prefix.starts_with(&prefix)) {
+            mentions.push(git::TaskRef {
+                prefix,
+                closes: false,
+            });
+        }
+    }
+
+    let mut linked: Vec<(Task, bool)> = Vec::new();
+    for mention in mentions {
+        match resolve_task(db, &mention.prefix) {
+            Ok(task) => match linked.iter_mut().find(|(t, _)| t.id == task.id) {
+                Some((_, closes)) => *closes |= mention.closes,
+                None => linked.push((task, mention.closes)),
+            },
+            Err(e) => eprintln!("zen:{}: {}", mention.prefix, e),
+        }
+    }
+
+    let hash = &commit.hash[..7.min(commit.hash.len())];
+    for (task, closes) in linked {
+        db.add_task_commit(&task.id, &commit)?;
+        println!("Linked {} to {} {}", hash, short_id(&task.id), task.title);
+        if !closes || task.status == TaskStatus::Done {
+            continue;
+        }
+        let completed = db.record(&format!("Mark '{}' DONE", task.title), |db| {
+            db.complete_task(&task)
+        })?;
+        if completed {
+            println!("Completed {} (+{} XP)", task.title, task.xp_reward);
+        } else {
+            eprintln!("{} is blocked by an unfinished task, left open", task.title);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,823 @@
+pub mod focus;
+mod git;
+pub mod remind;
+mod status;
+
+use crate::db::{
+    focus::focus_setting_max,
+    models::{Recurrence, Task, TaskPriority, TaskStatus},
+    Database,
+};
+use crate::inputs::quick_add::{extract_reward, parse_tags, QuickAdd};
+use crate::inputs::search::SearchQuery;
+use crate::interop::{self, backup, ics, markdown, taskwarrior, todotxt};
+use crate::utils::{
+    format_due, format_duration, format_local, parse_due_date, parse_span, short_id, timezone,
+    MAX_SPAN_DAYS,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use color_eyre::eyre::{bail, eyre, Result};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(name = "zenith-cli", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Create a new task (the title understands !h/!m/!l and due:<date>)
+    Add {
+        title: String,
+        #[arg(short, long, default_value = "")]
+        description: String,
+        #[arg(short, long, value_enum)]
+        priority: Option<PriorityArg>,
+        /// Due date (YYYY-MM-DD, today, tomorrow, fri, +3d)
+        #[arg(long)]
+        due: Option<String>,
+        /// XP awarded on completion (default 10)
+        #[arg(long, value_parser = clap::value_parser!(i32).range(0..))]
+        xp: Option<i32>,
+        /// Repeat rule (daily, weekdays, weekly:mon,wed, monthly:15, every:3d)
+        #[arg(long)]
+        repeat: Option<Recurrence>,
+        #[arg(long)]
+        project: Option<String>,
+        /// Comma separated tags
+        #[arg(long)]
+        tags: Option<String>,
+    },
+    /// List tasks, optionally filtered like the TUI search (project:x tag:y words)
+    List {
+        #[arg(short, long, value_enum)]
+        status: Option<StatusArg>,
+        /// Only open tasks whose blockers are all done (same as is:ready)
+        #[arg(long)]
+        ready: bool,
+        /// List archived tasks instead of active ones
+        #[arg(long)]
+        archived: bool,
+        query: Vec<String>,
+    },
+    /// Mark a task as DOING
+    Start {
+        id: String,
+        /// Also switch to a git branch named after the task
+        #[arg(long)]
+        branch: bool,
+    },
+    /// Mark a task as done and collect its XP
+    Done { id: String },
+    /// Edit the content of a task
+    Edit {
+        id: String,
+        #[arg(short, long)]
+        title: Option<String>,
+        #[arg(short, long)]
+        description: Option<String>,
+        #[arg(short, long, value_enum)]
+        priority: Option<PriorityArg>,
+        /// Due date (YYYY-MM-DD, today, tomorrow, fri, +3d), or "none" to clear it
+        #[arg(long)]
+        due: Option<String>,
+        /// XP awarded on completion
+        #[arg(long, value_parser = clap::value_parser!(i32).range(0..))]
+        xp: Option<i32>,
+        /// Repeat rule, or "none" to stop repeating
+        #[arg(long)]
+        repeat: Option<String>,
+        /// Project name, or "none" to clear it
+        #[arg(long)]
+        project: Option<String>,
+        /// Comma separated tags, replacing the current ones
+        #[arg(long)]
+        tags: Option<String>,
+    },
+    /// Move a task to the Trash
+    Rm { id: String },
+    /// Move a finished task to the Archive
+    Archive { id: String },
+    /// Bring a task back from the Archive
+    Unarchive { id: String },
+    /// List the tasks in the Trash
+    Trash,
+    /// Bring a task back from the Trash
+    Restore { id: String },
+    /// Mark a task as blocked by another one
+    Block { id: String, blocked_by: String },
+    /// Remove a blocked-by relationship
+    Unblock { id: String, blocked_by: String },
+    /// Silence a task's reminders for a while (default: the `snooze` setting)
+    Snooze {
+        id: String,
+        /// How long, like 30m, 2h or 1d
+        #[arg(long = "for")]
+        length: Option<String>,
+    },
+    /// Send the due-date reminders that are due
+    Remind {
+        /// Keep running and check every minute
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Summarise clocked time per task per day
+    Log {
+        /// How many days back to include, up to 3650
+        #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(i64).range(1..=3650))]
+        days: i64,
+    },
+    /// Export the whole database
+    Export {
+        #[arg(short, long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Import tasks from an export, matching existing tasks by id
+    Import {
+        file: PathBuf,
+        /// Defaults to the file extension
+        #[arg(short, long, value_enum)]
+        format: Option<Format>,
+        /// Only print what would change
+        #[arg(long)]
+        dry_run: bool,
+        /// Also replace the profile (level and XP) and settings with the
+        /// ones in a JSON export
+        #[arg(long)]
+        with_profile: bool,
+    },
+    /// Sync a Markdown checklist (`- [ ]` / `- [x]` items) with the tasks
+    SyncMd { file: PathBuf },
+    /// Revert the last change (from the CLI or the TUI)
+    Undo,
+    /// Re-apply the last undone change
+    Redo,
+    /// One-line summary for shell prompts and status bars
+    Status {
+        /// Placeholders: {streak} {today} {level} {xp} {active_task}
+        /// {focus_phase} {focus_remaining}
+        #[arg(long, default_value = status::DEFAULT_FORMAT)]
+        format: String,
+        /// Print everything as JSON instead
+        #[arg(long)]
+        json: bool,
+    },
+    /// Drive the Pomodoro timer without the TUI
+    Focus {
+        #[command(subcommand)]
+        action: FocusAction,
+    },
+    /// Link commits that mention `zen:<id>` to their tasks
+    Git {
+        #[command(subcommand)]
+        action: GitAction,
+    },
+    /// Show or change settings (no arguments lists them all)
+    Config {
+        key: Option<String>,
+        value: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum FocusAction {
+    /// Start or resume the timer, optionally on a task
+    Start { id: Option<String> },
+    /// Pause the timer
+    Pause,
+    /// Show the phase, round and time left
+    Status,
+    /// Background process that sends the notifications (started by `start`)
+    #[command(hide = true)]
+    Notifier,
+}
+
+#[derive(Subcommand)]
+pub enum GitAction {
+    /// Manage the repository's commit hooks
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+    /// Checks a commit message's task mentions (run by the commit-msg hook)
+    #[command(hide = true)]
+    CommitMsg { file: PathBuf },
+    /// Links HEAD to the tasks it mentions (run by the post-commit hook)
+    #[command(hide = true)]
+    PostCommit,
+}
+
+#[derive(Subcommand)]
+pub enum HookAction {
+    /// Install the commit-msg and post-commit hooks in the current repository
+    Install {
+        /// Replace hooks that weren't installed by zenith-cli
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the hooks again
+    Uninstall,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    Json,
+    Csv,
+    /// iCalendar VTODOs, for calendar apps
+    Ics,
+    /// The JSON that `task export` writes and `task import` reads
+    Taskwarrior,
+    /// One todo.txt line per task
+    Todotxt,
+}
+
+impl Format {
+    /// Guesses from the extension; a `.json` file holding an array rather
+    /// than a Zenith snapshot is a Taskwarrior export.
+    fn detect(path: &Path, contents: &str) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Format::Csv,
+            Some(ext) if ext.eq_ignore_ascii_case("ics") => Format::Ics,
+            Some(ext) if ext.eq_ignore_ascii_case("txt") => Format::Todotxt,
+            _ if contents.trim_start().starts_with('[') => Format::Taskwarrior,
+            _ => Format::Json,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum PriorityArg {
+    Low,
+    Medium,
+    High,
+}
+
+impl From<PriorityArg> for TaskPriority {
+    fn from(p: PriorityArg) -> Self {
+        match p {
+            PriorityArg::Low => TaskPriority::Low,
+            PriorityArg::Medium => TaskPriority::Medium,
+            PriorityArg::High => TaskPriority::High,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StatusArg {
+    Todo,
+    Doing,
+    Done,
+}
+
+impl From<StatusArg> for TaskStatus {
+    fn from(s: StatusArg) -> Self {
+        match s {
+            StatusArg::Todo => TaskStatus::Todo,
+            StatusArg::Doing => TaskStatus::Doing,
+            StatusArg::Done => TaskStatus::Done,
+        }
+    }
+}
+
+pub fn run(command: Command) -> Result<()> {
+    // Read-only and skips the startup work that `init` does
+    if let Command::Status { format, json } = &command {
+        return status::run(format, *json);
+    }
+    // Runs on every `git commit`, so it only opens the database when it must
+    if let Command::Git {
+        action: GitAction::CommitMsg { file },
+    } = &command
+    {
+        return git::check_commit_msg(file);
+    }
+    execute(&Database::init()?, command)
+}
+
+fn execute(db: &Database, command: Command) -> Result<()> {
+    match command {
+        Command::Add {
+            title,
+            description,
+            priority,
+            due,
+            xp,
+            repeat,
+            project,
+            tags,
+        } => {
+            let quick_add = QuickAdd::parse(&title);
+            let (description, reward) = extract_reward(&description);
+            let due_date = match due {
+                Some(s) => Some(parse_due(&s)?),
+                None => quick_add.due_date,
+            };
+            let priority = priority
+                .map(Into::into)
+                .or(quick_add.priority)
+                .unwrap_or(TaskPriority::Medium);
+            let xp = xp.or(reward).unwrap_or(10);
+            let mut task = Task::new(quick_add.title, description, priority, xp, due_date);
+            task.recurrence = repeat;
+            task.project = project.or(quick_add.project);
+            task.tags = parse_tags(tags.as_deref().unwrap_or(""));
+            for tag in quick_add.tags {
+                if !task.tags.contains(&tag) {
+                    task.tags.push(tag);
+                }
+            }
+            db.record(&format!("Add '{}'", task.title), |db| db.create_task(&task))?;
+            println!("Created {} {}", short_id(&task.id), task.title);
+        }
+        Command::List {
+            status,
+            ready,
+            archived,
+            query,
+        } => {
+            let status: Option<TaskStatus> = status.map(Into::into);
+            let mut query = SearchQuery::parse(&query.join(" "));
+            query.ready |= ready;
+            let blocked = db.get_blocked_task_ids()?;
+            let tasks = if archived {
+                db.get_archived_tasks()?
+            } else {
+                db.get_all_tasks()?
+            };
+            for task in tasks
+                .iter()
+                .filter(|t| status.as_ref().is_none_or(|s| &t.status == s))
+                .filter(|t| query.matches(t, &blocked))
+            {
+                print_task(task, blocked.contains(&task.id));
+            }
+        }
+        Command::Start { id, branch } => {
+            let task = resolve_task(db, &id)?;
+            if db.get_blocked_task_ids()?.contains(&task.id) {
+                bail!("{} is blocked by an unfinished task", task.title);
+            }
+            if task.status != TaskStatus::Doing {
+                db.record(&format!("Mark '{}' DOING", task.title), |db| {
+                    db.update_task_status(&task.id, TaskStatus::Doing)
+                })?;
+                println!("Started {}", task.title);
+            } else {
+                println!("Already in progress: {}", task.title);
+            }
+            if branch || db.get_setting("git_branch_on_start")?.as_deref() == Some("true") {
+                println!(
+                    "Switched to branch {}",
+                    interop::git::switch_to_branch(&task)?
+                );
+            }
+        }
+        Command::Done { id } => {
+            let task = resolve_task(db, &id)?;
+            if task.status == TaskStatus::Done {
+                println!("Already done: {}", task.title);
+            } else {
+                let completed = db.record(&format!("Mark '{}' DONE", task.title), |db| {
+                    db.complete_task(&task)
+                })?;
+                if !completed {
+                    bail!("{} is blocked by an unfinished task", task.title);
+                }
+                println!("Completed {} (+{} XP)", task.title, task.xp_reward);
+            }
+        }
+        Command::Edit {
+            id,
+            title,
+            description,
+            priority,
+            due,
+            xp,
+            repeat,
+            project,
+            tags,
+        } => {
+            let task = resolve_task(db, &id)?;
+            let due_date = match due.as_deref() {
+                Some("none") => None,
+                Some(s) => Some(parse_due(s)?),
+                None => task.due_date,
+            };
+            let recurrence = match repeat.as_deref() {
+                Some("none") => None,
+                Some(s) => Some(s.parse::<Recurrence>().map_err(|e| eyre!(e))?),
+                None => task.recurrence.clone(),
+            };
+            let project = match project.as_deref() {
+                Some("none") => None,
+                Some(p) => Some(p),
+                None => task.project.as_deref(),
+            };
+            let tags = tags.as_deref().map(parse_tags).unwrap_or(task.tags.clone());
+            let title = title.as_deref().unwrap_or(&task.title);
+            db.record(&format!("Edit '{}'", title), |db| {
+                db.update_task_content(
+                    &task.id,
+                    title,
+                    description.as_deref().unwrap_or(&task.description),
+                    priority.map(Into::into).unwrap_or(task.priority),
+                    xp.unwrap_or(task.xp_reward),
+                    due_date,
+                    recurrence.as_ref(),
+                )?;
+                db.set_task_labels(&task.id, project, &tags)
+            })?;
+            println!("Updated {}", short_id(&task.id));
+        }
+        Command::Rm { id } => {
+            let task = resolve_task(db, &id)?;
+            db.record(&format!("Delete '{}'", task.title), |db| {
+                db.delete_task(&task.id)
+            })?;
+            println!("Moved {} {} to the Trash", short_id(&task.id), task.title);
+        }
+        Command::Trash => {
+            for task in db.get_trashed_tasks()? {
+                print_task(&task, false);
+            }
+        }
+        Command::Archive { id } => {
+            let task = resolve_task(db, &id)?;
+            if task.status != TaskStatus::Done {
+                bail!("Only finished tasks can be archived");
+            }
+            db.record(&format!("Archive '{}'", task.title), |db| {
+                db.archive_task(&task.id)
+            })?;
+            println!("Archived {} {}", short_id(&task.id), task.title);
+        }
+        Command::Unarchive { id } => {
+            let task = find_by_prefix(db.get_archived_tasks()?, &id, "in the Archive ")?;
+            db.record(&format!("Unarchive '{}'", task.title), |db| {
+                db.unarchive_task(&task.id)
+            })?;
+            println!("Unarchived {} {}", short_id(&task.id), task.title);
+        }
+        Command::Restore { id } => {
+            let task = find_by_prefix(db.get_trashed_tasks()?, &id, "in the Trash ")?;
+            db.record(&format!("Restore '{}'", task.title), |db| {
+                db.restore_task(&task.id)
+            })?;
+            println!("Restored {} {}", short_id(&task.id), task.title);
+        }
+        Command::Block { id, blocked_by } => {
+            let task = resolve_task(db, &id)?;
+            let blocker = resolve_task(db, &blocked_by)?;
+            db.record("Add dependency", |db| {
+                db.add_dependency(&task.id, &blocker.id)
+            })?;
+            println!("{} is now blocked by {}", task.title, blocker.title);
+        }
+        Command::Unblock { id, blocked_by } => {
+            let task = resolve_task(db, &id)?;
+            let blocker = resolve_task(db, &blocked_by)?;
+            db.record("Remove dependency", |db| {
+                db.remove_dependency(&task.id, &blocker.id)
+            })?;
+            println!("{} is no longer blocked by {}", task.title, blocker.title);
+        }
+        Command::Snooze { id, length } => {
+            let task = resolve_task(db, &id)?;
+            if task.due_date.is_none() {
+                bail!("{} has no due date to be reminded of", task.title);
+            }
+            let length = match length {
+                Some(s) => parse_span(&s)
+                    .filter(|d| *d > Duration::zero())
+                    .ok_or_else(|| {
+                        eyre!(
+                            "Invalid length '{}'. Use 30m, 2h or 1d, up to {}d",
+                            s,
+                            MAX_SPAN_DAYS
+                        )
+                    })?,
+                None => db.snooze_length()?,
+            };
+            let until = Utc::now()
+                .checked_add_signed(length)
+                .ok_or_else(|| eyre!("Can't snooze that long"))?;
+            db.snooze_task(&task.id, until)?;
+            println!(
+                "Snoozed {} until {}",
+                task.title,
+                format_local(until, "%a %b %d %H:%M")
+            );
+        }
+        Command::Remind { watch } => remind::run(db, watch)?,
+        Command::Log { days } => {
+            let titles: HashMap<String, String> = db
+                .get_every_task()?
+                .into_iter()
+                .map(|t| (t.id, t.title))
+                .collect();
+            let first_day = timezone::today() - Duration::days(days - 1);
+            let since = timezone::to_utc(first_day.and_hms_opt(0, 0, 0).unwrap());
+            // day -> task -> seconds, both in first-clocked order
+            let mut log: Vec<(NaiveDate, Vec<(String, i64)>)> = Vec::new();
+            for entry in db.get_time_entries(since)? {
+                let day = timezone::local(entry.started_at).date();
+                if log.last().is_none_or(|(d, _)| *d != day) {
+                    log.push((day, Vec::new()));
+                }
+                let tasks = &mut log.last_mut().unwrap().1;
+                match tasks.iter_mut().find(|(id, _)| *id == entry.task_id) {
+                    Some((_, seconds)) => *seconds += entry.seconds(),
+                    None => tasks.push((entry.task_id.clone(), entry.seconds())),
+                }
+            }
+            if log.is_empty() {
+                println!("No time clocked in the last {} days", days);
+            }
+            for (day, tasks) in log {
+                let total: i64 = tasks.iter().map(|(_, s)| s).sum();
+                println!("{}  {}", day.format("%a %Y-%m-%d"), format_duration(total));
+                for (id, seconds) in tasks {
+                    let title = titles.get(&id).map(String::as_str).unwrap_or("?");
+                    println!("  {:>8}  {}", format_duration(seconds), title);
+                }
+            }
+        }
+        Command::Export { format, output } => {
+            let out: Box<dyn Write> = match output {
+                Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(io::stdout().lock()),
+            };
+            match format {
+                Format::Json => backup::write_json(db, out)?,
+                Format::Csv => backup::write_csv(db, out)?,
+                Format::Ics => ics::write_ics(db, out)?,
+                Format::Taskwarrior => taskwarrior::write_taskwarrior(db, out)?,
+                Format::Todotxt => todotxt::write_todotxt(db, out)?,
+            }
+        }
+        Command::Import {
+            file,
+            format,
+            dry_run,
+            with_profile,
+        } => {
+            let contents = fs::read_to_string(&file)?;
+            let input = contents.as_bytes();
+            let format = format.unwrap_or_else(|| Format::detect(&file, &contents));
+            if with_profile && format != Format::Json {
+                bail!("--with-profile only applies to JSON exports");
+            }
+            let summary = match format {
+                Format::Json => {
+                    let snapshot = backup::read_json(input)?;
+                    for (key, value) in &snapshot.settings {
+                        check_setting(key, value)?;
+                    }
+                    db.record("Import", |db| {
+                        if with_profile && !dry_run {
+                            if let Some(profile) = &snapshot.profile {
+                                db.set_user_profile(profile)?;
+                            }
+                            for (key, value) in &snapshot.settings {
+                                db.set_setting(key, value)?;
+                            }
+                        }
+                        interop::import_tasks(db, snapshot.tasks, dry_run)
+                    })?
+                }
+                Format::Csv => {
+                    let tasks = backup::read_csv(input)?;
+                    db.record("Import", |db| interop::import_tasks(db, tasks, dry_run))?
+                }
+                Format::Ics => {
+                    let items = ics::read_ics(input)?;
+                    db.record("Import", |db| ics::import_ics(db, items, dry_run))?
+                }
+                Format::Taskwarrior => {
+                    let tasks = taskwarrior::read_taskwarrior(input)?;
+                    db.record("Import", |db| {
+                        taskwarrior::import_taskwarrior(db, tasks, dry_run)
+                    })?
+                }
+                Format::Todotxt => {
+                    let tasks = todotxt::read_todotxt(input)?;
+                    db.record("Import", |db| todotxt::import_todotxt(db, tasks, dry_run))?
+                }
+            };
+            println!("{}", summary);
+        }
+        Command::SyncMd { file } => {
+            let contents = fs::read_to_string(&file)?;
+            let path = fs::canonicalize(&file)?;
+            // The markers are staged next to the file while the sync is still
+            // in its transaction, and only moved into place once it's saved,
+            // so the file and the links can't disagree
+            let staged = path.with_file_name(format!(
+                ".{}.zenith-sync",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ));
+            let result = db.record(&format!("Sync {}", file.display()), |db| {
+                let (synced, summary) = markdown::sync(db, &path.to_string_lossy(), &contents)?;
+                let changed = synced != contents;
+                if changed {
+                    fs::write(&staged, synced)?;
+                    fs::set_permissions(&staged, fs::metadata(&path)?.permissions())?;
+                }
+                Ok((changed, summary))
+            });
+            let (changed, summary) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    let _ = fs::remove_file(&staged);
+                    return Err(e);
+                }
+            };
+            if changed {
+                fs::rename(&staged, &path)?;
+            }
+            println!("{}", summary);
+        }
+        Command::Undo => match db.undo()? {
+            Some(label) => println!("Undid: {}", label),
+            None => println!("Nothing to undo"),
+        },
+        Command::Redo => match db.redo()? {
+            Some(label) => println!("Redid: {}", label),
+            None => println!("Nothing to redo"),
+        },
+        Command::Status { .. } => unreachable!("handled before the database is opened"),
+        Command::Focus { action } => focus::run(db, action)?,
+        Command::Git { action } => git::run(db, action)?,
+        Command::Config { key, value } => match (key, value) {
+            (None, _) => {
+                for (key, value) in db.get_all_settings()? {
+                    println!("{} = {}", key, value);
+                }
+            }
+            (Some(key), None) => match db.get_setting(&key)? {
+                Some(value) => println!("{}", value),
+                None => bail!("Setting '{}' is not set", key),
+            },
+            (Some(key), Some(value)) => {
+                check_setting(&key, &value)?;
+                db.set_setting(&key, &value)?;
+                println!("{} = {}", key, value);
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// Rejects values the app can't use, for `config` and for settings that
+/// arrive with an import.
+fn check_setting(key: &str, value: &str) -> Result<()> {
+    match key {
+        "timezone" if !value.is_empty() && !timezone::is_valid(value) => bail!(
+            "Unknown timezone '{}'. Use an IANA name like Europe/Berlin",
+            value
+        ),
+        "reminders" if !value.is_empty() && value.split(',').any(|s| parse_span(s).is_none()) => {
+            bail!(
+                "Invalid reminders '{}'. Use offsets like 1d,1h,15m, up to {}d",
+                value,
+                MAX_SPAN_DAYS
+            )
+        }
+        "snooze" if parse_span(value).is_none() => {
+            bail!(
+                "Invalid snooze '{}'. Use 30m, 2h or 1d, up to {}d",
+                value,
+                MAX_SPAN_DAYS
+            )
+        }
+        _ => match focus_setting_max(key) {
+            Some(max) if !value.parse::<i64>().is_ok_and(|v| (1..=max).contains(&v)) => {
+                bail!(
+                    "Invalid {} '{}'. Use a whole number from 1 to {}",
+                    key,
+                    value,
+                    max
+                )
+            }
+            _ => Ok(()),
+        },
+    }
+}
+
+/// Finds the single active task whose id starts with `prefix`.
+fn resolve_task(db: &Database, prefix: &str) -> Result<Task> {
+    find_by_prefix(db.get_all_tasks()?, prefix, "")
+}
+
+/// `place` completes the "No task ...matches" error, e.g. `"in the Trash "`.
+fn find_by_prefix(tasks: Vec<Task>, prefix: &str, place: &str) -> Result<Task> {
+    let mut matches: Vec<Task> = tasks
+        .into_iter()
+        .filter(|t| t.id.starts_with(prefix))
+        .collect();
+
+    match matches.len() {
+        0 => bail!("No task {}matches id '{}'", place, prefix),
+        1 => Ok(matches.remove(0)),
+        n => bail!("Id '{}' is ambiguous ({} tasks match)", prefix, n),
+    }
+}
+
+fn parse_due(s: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    match parse_due_date(s) {
+        Some(d) => Ok(d),
+        None => bail!(
+            "Invalid due date '{}', expected e.g. 2026-11-01 14:00, today, tomorrow 9am, next fri, eow, in 3 days or +3d",
+            s
+        ),
+    }
+}
+
+fn print_task(task: &Task, blocked: bool) {
+    let due_str = task
+        .due_date
+        .map(|d| format_due(d, "  due %Y-%m-%d"))
+        .unwrap_or_default();
+    let repeat_str = task
+        .recurrence
+        .as_ref()
+        .map(|r| format!("  ↻ {}", r.label()))
+        .unwrap_or_default();
+    let mut labels = String::new();
+    if let Some(project) = &task.project {
+        labels.push_str(&format!(" @{}", project));
+    }
+    for tag in &task.tags {
+        labels.push_str(&format!(" #{}", tag));
+    }
+    println!(
+        "{}  {:<5}  {:<6}  {}{}{}{}{}",
+        short_id(&task.id),
+        task.status.to_string(),
+        task.priority.to_string(),
+        if blocked { "🔒 " } else { "" },
+        task.title,
+        labels,
+        due_str,
+        repeat_str
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_in(db: &Database, args: &[&str]) -> Result<()> {
+        let cli = Cli::try_parse_from(std::iter::once("zenith-cli").chain(args.iter().copied()))?;
+        execute(db, cli.command.unwrap())
+    }
+
+    #[test]
+    fn add_ignores_a_negative_reward() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(run_in(&db, &["add", "Cheat", "--xp=-5"]).is_err());
+
+        run_in(&db, &["add", "Cheat", "--description", "> Reward: -50"]).unwrap();
+        let task = db.get_every_task().unwrap().pop().unwrap();
+        assert_eq!(task.xp_reward, 10);
+        assert_eq!(task.description, "> Reward: -50");
+
+        run_in(&db, &["done", &task.id]).unwrap();
+        assert_eq!(db.get_user_profile().unwrap().current_xp, 10);
+    }
+
+    #[test]
+    fn sync_md_links_nothing_when_the_file_cannot_be_written() {
+        let dir = std::env::temp_dir().join(format!("zenith-{}", uuid::Uuid::new_v4()));
+        fs::create_dir(&dir).unwrap();
+        let file = dir.join("todo.md");
+        fs::write(&file, "- [ ] Buy milk\n").unwrap();
+        let file_arg = file.to_str().unwrap();
+
+        // A directory where the staged copy goes makes the write fail
+        let staged = dir.join(".todo.md.zenith-sync");
+        fs::create_dir(&staged).unwrap();
+        let db = Database::open_in_memory().unwrap();
+        assert!(run_in(&db, &["sync-md", file_arg]).is_err());
+        assert!(db.get_every_task().unwrap().is_empty());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "- [ ] Buy milk\n");
+
+        fs::remove_dir(&staged).unwrap();
+        run_in(&db, &["sync-md", file_arg]).unwrap();
+        run_in(&db, &["sync-md", file_arg]).unwrap();
+        assert_eq!(db.get_every_task().unwrap().len(), 1);
+        assert!(fs::read_to_string(&file).unwrap().contains("<!-- zenith:"));
+        assert!(!staged.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,55 @@
+use crate::db::reminders::Reminder;
+use crate::db::Database;
+use crate::utils::format_due;
+use chrono::Utc;
+use color_eyre::eyre::Result;
+use notify_rust::Notification;
+use std::thread;
+use std::time::Duration;
+
+/// How often `remind --watch` looks for due reminders.
+const WATCH_INTERVAL_SEC: u64 = 60;
+
+/// Sends the reminders that are due, then keeps doing so every minute with
+/// `watch` (for running from a login script or a service manager).
+pub fn run(db: &Database, watch: bool) -> Result<()> {
+    loop {
+        for reminder in db.take_due_reminders(Utc::now())? {
+            println!("{}: {}", reminder.task.title, reminder_text(&reminder));
+            notify_reminder(&reminder);
+        }
+        if !watch {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(WATCH_INTERVAL_SEC));
+    }
+}
+
+/// "Due Mon Oct 19 09:00", "Overdue since …", or "Overdue by 3 days (…)".
+pub fn reminder_text(reminder: &Reminder) -> String {
+    let due = reminder
+        .task
+        .due_date
+        .map(|d| format_due(d, "%a %b %d"))
+        .unwrap_or_default();
+    match reminder.days_overdue {
+        None => format!("Due {}", due),
+        Some(0) => format!("Overdue since {}", due),
+        Some(1) => format!("Overdue by 1 day (due {})", due),
+        Some(days) => format!("Overdue by {} days (due {})", days, due),
+    }
+}
+
+/// Desktop notification for a reminder. Overdue tasks escalate to critical
+/// urgency once they're a day late (where the desktop supports it).
+pub fn notify_reminder(reminder: &Reminder) {
+    let mut notification = Notification::new();
+    notification
+        .summary(&format!("Zenith: {}", reminder.task.title))
+        .body(&reminder_text(reminder));
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if reminder.days_overdue.is_some_and(|days| days > 0) {
+        notification.urgency(notify_rust::Urgency::Critical);
+    }
+    let _ = notification.show();
+}
@@ -0,0 +1,62 @@
+use crate::db::Database;
+use color_eyre::eyre::Result;
+use serde_json::json;
+
+/// What `status` prints when no `--format` is given.
+pub const DEFAULT_FORMAT: &str = "🔥 {streak}  ✔ {today}  {active_task}  {focus_remaining}";
+
+/// One-line summary for shell prompts and status bars. Opens the database
+/// read-only and nothing else, so it stays cheap to call on every refresh.
+pub fn run(format: &str, as_json: bool) -> Result<()> {
+    let db = Database::open_read_only()?;
+    let streak = db.get_streak()?;
+    let today = db.get_tasks_today()?;
+    let profile = db.get_user_profile()?;
+    let active = db.get_doing_task()?;
+    let timer = db.get_focus_timer()?;
+    let remaining = timer.remaining();
+
+    if as_json {
+        let focus = timer.is_running().then(|| {
+            json!({
+                "phase": timer.phase.to_string().to_lowercase(),
+                "round": timer.round,
+                "remaining_sec": remaining,
+            })
+        });
+        let active = active.map(|t| json!({ "id": t.id, "title": t.title }));
+        let summary = json!({
+            "streak": streak,
+            "today": today,
+            "level": profile.level,
+            "xp": profile.current_xp,
+            "next_level_xp": profile.next_level_xp,
+            "active_task": active,
+            "focus": focus,
+        });
+        println!("{}", summary);
+        return Ok(());
+    }
+
+    let (focus_phase, focus_remaining) = if timer.is_running() {
+        (
+            timer.phase.label().to_lowercase(),
+            format!("{:02}:{:02}", remaining / 60, remaining % 60),
+        )
+    } else {
+        (String::new(), String::new())
+    };
+    let line = format
+        .replace("{streak}", &streak.to_string())
+        .replace("{today}", &today.to_string())
+        .replace("{level}", &profile.level.to_string())
+        .replace("{xp}", &profile.current_xp.to_string())
+        .replace(
+            "{active_task}",
+            active.as_ref().map_or("", |t| t.title.as_str()),
+        )
+        .replace("{focus_phase}", &focus_phase)
+        .replace("{focus_remaining}", &focus_remaining);
+    println!("{}", line.trim_end());
+    Ok(())
+}
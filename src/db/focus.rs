@@ -0,0 +1,391 @@
+use crate::db::models::{FocusConfig, FocusPhase, FocusSession, FocusTimer};
+use crate::db::{parse_time, Database};
+use chrono::{Duration, Utc};
+use color_eyre::eyre::Result;
+use rusqlite::{params, OptionalExtension};
+
+/// XP for a Pomodoro that runs all the way to zero.
+pub const FOCUS_BONUS_XP: i32 = 5;
+
+/// How long a notifier heartbeat stays valid.
+const NOTIFIER_TIMEOUT_SEC: i64 = 15;
+
+/// The largest value a focus setting takes: a phase can last a day, a cycle
+/// a hundred rounds. `None` for keys that aren't focus settings.
+pub fn focus_setting_max(key: &str) -> Option<i64> {
+    match key {
+        "focus_work_minutes" | "focus_short_break_minutes" | "focus_long_break_minutes" => {
+            Some(24 * 60)
+        }
+        "focus_long_break_every" => Some(100),
+        _ => None,
+    }
+}
+
+impl Database {
+    pub fn focus_config(&self) -> Result<FocusConfig> {
+        // Out-of-range values fall back to the default
+        let setting = |key: &str, default: i64| -> Result<i64> {
+            let max = focus_setting_max(key).unwrap_or(default);
+            Ok(self
+                .get_setting(key)?
+                .and_then(|v| v.parse::<i64>().ok())
+                .filter(|v| (1..=max).contains(v))
+                .unwrap_or(default))
+        };
+        Ok(FocusConfig {
+            work_sec: setting("focus_work_minutes", 25)? * 60,
+            short_break_sec: setting("focus_short_break_minutes", 5)? * 60,
+            long_break_sec: setting("focus_long_break_minutes", 15)? * 60,
+            long_break_every: u32::try_from(setting("focus_long_break_every", 4)?)?,
+        })
+    }
+
+    /// The stored timer, or a fresh paused work phase if there is none yet.
+    pub fn get_focus_timer(&self) -> Result<FocusTimer> {
+        let timer = self
+            .conn
+            .query_row(
+                "SELECT phase, round, duration_sec, remaining_sec, running_since,
+                        session_start, session_task, clocked_task
+                 FROM focus_timer WHERE id = 1",
+                [],
+                |row| {
+                    Ok(FocusTimer {
+                        phase: row.get(0)?,
+                        round: row.get(1)?,
+                        duration_sec: row.get(2)?,
+                        remaining_sec: row.get(3)?,
+                        running_since: row
+                            .get::<_, Option<String>>(4)?
+                            .map(|s| parse_time(&s))
+                            .transpose()?,
+                        session_start: row
+                            .get::<_, Option<String>>(5)?
+                            .map(|s| parse_time(&s))
+                            .transpose()?,
+                        session_task: row.get(6)?,
+                        clocked_task: row.get(7)?,
+                    })
+                },
+            )
+            .optional()?;
+        match timer {
+            Some(timer) => Ok(timer),
+            None => Ok(FocusTimer::new(&self.focus_config()?)),
+        }
+    }
+
+    fn save_focus_timer(&self, timer: &FocusTimer) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO focus_timer (id, phase, round, duration_sec, remaining_sec,
+                                      running_since, session_start, session_task, clocked_task)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(id) DO UPDATE SET
+                phase = excluded.phase,
+                round = excluded.round,
+                duration_sec = excluded.duration_sec,
+                remaining_sec = excluded.remaining_sec,
+                running_since = excluded.running_since,
+                session_start = excluded.session_start,
+                session_task = excluded.session_task,
+                clocked_task = excluded.clocked_task",
+            params![
+                timer.phase,
+                timer.round,
+                timer.duration_sec,
+                timer.remaining_sec,
+                timer.running_since.map(|d| d.to_rfc3339()),
+                timer.session_start.map(|d| d.to_rfc3339()),
+                timer.session_task,
+                timer.clocked_task,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Starts or resumes the timer. A work phase clocks in on `task_id` (or the
+    /// task of the previous round) unless that task is already clocked in.
+    pub fn start_focus(&self, task_id: Option<&str>) -> Result<()> {
+        let mut timer = self.get_focus_timer()?;
+        if timer.is_running() {
+            return Ok(());
+        }
+        timer.running_since = Some(Utc::now());
+        if timer.phase == FocusPhase::Work {
+            if timer.session_start.is_none() {
+                timer.session_start = Some(Utc::now());
+                if let Some(id) = task_id {
+                    timer.session_task = Some(id.to_string());
+                }
+            }
+            if let Some(id) = timer.session_task.clone() {
+                if self.running_clock()?.is_none_or(|c| c.task_id != id) {
+                    self.start_clock(&id)?;
+                    timer.clocked_task = Some(id);
+                }
+            }
+        }
+        self.save_focus_timer(&timer)
+    }
+
+    pub fn pause_focus(&self) -> Result<()> {
+        let mut timer = self.get_focus_timer()?;
+        timer.remaining_sec = timer.remaining();
+        timer.running_since = None;
+        self.stop_focus_clock(&mut timer)?;
+        self.save_focus_timer(&timer)
+    }
+
+    /// Restarts the current phase from its configured length. An unfinished
+    /// work phase is recorded as aborted.
+    pub fn reset_focus(&self) -> Result<()> {
+        let mut timer = self.get_focus_timer()?;
+        self.end_focus_session(&mut timer, false)?;
+        timer.running_since = None;
+        timer.duration_sec = self.focus_config()?.duration(timer.phase);
+        timer.remaining_sec = timer.duration_sec;
+        self.save_focus_timer(&timer)
+    }
+
+    /// Ends the current phase early. A skipped work phase is recorded as
+    /// aborted; the next phase runs if the timer was running.
+    pub fn skip_focus(&self) -> Result<FocusPhase> {
+        let mut timer = self.get_focus_timer()?;
+        self.finish_phase(&mut timer, false)?;
+        Ok(timer.phase)
+    }
+
+    pub fn extend_focus(&self, seconds: i64) -> Result<()> {
+        let mut timer = self.get_focus_timer()?;
+        timer.duration_sec += seconds;
+        timer.remaining_sec += seconds;
+        self.save_focus_timer(&timer)
+    }
+
+    /// Completes the running phase once it reaches zero and moves on to the
+    /// next one. Returns the phase that finished, so exactly one caller (the
+    /// TUI or the notifier, whichever gets there first) notifies about it.
+    pub fn tick_focus(&self) -> Result<Option<FocusPhase>> {
+        let mut timer = self.get_focus_timer()?;
+        if !timer.is_running() || timer.remaining() > 0 {
+            return Ok(None);
+        }
+        // Claim the transition by stopping the stored timer; if another
+        // process got there first the row no longer matches and it's theirs.
+        let claimed = self.conn.execute(
+            "UPDATE focus_timer SET running_since = NULL, remaining_sec = 0
+             WHERE id = 1 AND running_since = ?1",
+            params![timer.running_since.map(|d| d.to_rfc3339())],
+        )?;
+        if claimed == 0 {
+            return Ok(None);
+        }
+        let finished = timer.phase;
+        self.finish_phase(&mut timer, true)?;
+        Ok(Some(finished))
+    }
+
+    fn finish_phase(&self, timer: &mut FocusTimer, completed: bool) -> Result<()> {
+        let was_running = timer.is_running();
+        if timer.phase == FocusPhase::Work {
+            self.end_focus_session(timer, completed)?;
+        }
+
+        // Work → short break → work … → long break after `long_break_every`
+        // rounds, then back to round 1.
+        let config = self.focus_config()?;
+        (timer.phase, timer.round) = match timer.phase {
+            FocusPhase::Work if timer.round >= config.long_break_every => {
+                (FocusPhase::LongBreak, timer.round)
+            }
+            FocusPhase::Work => (FocusPhase::ShortBreak, timer.round),
+            FocusPhase::ShortBreak => (FocusPhase::Work, timer.round + 1),
+            FocusPhase::LongBreak => (FocusPhase::Work, 1),
+        };
+        timer.running_since = None;
+        timer.duration_sec = config.duration(timer.phase);
+        timer.remaining_sec = timer.duration_sec;
+        self.save_focus_timer(timer)?;
+
+        if was_running {
+            self.start_focus(None)?;
+        }
+        Ok(())
+    }
+
+    /// Stores the session and, if it ran to zero, awards `FOCUS_BONUS_XP`.
+    /// A session that never ticked isn't worth keeping. Neither goes through
+    /// the undo journal, which couldn't take the session back with the XP.
+    fn end_focus_session(&self, timer: &mut FocusTimer, completed: bool) -> Result<()> {
+        self.stop_focus_clock(timer)?;
+        let Some(started_at) = timer.session_start.take() else {
+            return Ok(());
+        };
+        let session = FocusSession {
+            task_id: timer.session_task.clone(),
+            started_at,
+            planned_sec: timer.duration_sec,
+            actual_sec: timer.duration_sec - timer.remaining(),
+            completed,
+        };
+        if session.actual_sec == 0 {
+            return Ok(());
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.add_focus_session(&session)?;
+        if completed {
+            self.add_xp(FOCUS_BONUS_XP)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Clocks out if the focus session was what clocked in.
+    fn stop_focus_clock(&self, timer: &mut FocusTimer) -> Result<()> {
+        if let Some(id) = timer.clocked_task.take() {
+            if self.running_clock()?.is_some_and(|c| c.task_id == id) {
+                self.stop_clock()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks the notifier as alive, or gone when `alive` is false.
+    pub fn touch_focus_notifier(&self, alive: bool) -> Result<()> {
+        self.conn.execute(
+            "UPDATE focus_timer SET notifier_seen_at = ?1 WHERE id = 1",
+            params![alive.then(|| Utc::now().to_rfc3339())],
+        )?;
+        Ok(())
+    }
+
+    pub fn focus_notifier_alive(&self) -> Result<bool> {
+        let seen: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT notifier_seen_at FROM focus_timer WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        let seen = seen.map(|s| parse_time(&s)).transpose()?;
+        Ok(seen.is_some_and(|t| Utc::now() - t < Duration::seconds(NOTIFIER_TIMEOUT_SEC)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::{Task, TaskPriority};
+
+    /// Pretends the running phase started `seconds` earlier.
+    fn run_for(db: &Database, seconds: i64) {
+        let mut timer = db.get_focus_timer().unwrap();
+        timer.running_since = timer.running_since.map(|t| t - Duration::seconds(seconds));
+        timer.session_start = timer.session_start.map(|t| t - Duration::seconds(seconds));
+        db.save_focus_timer(&timer).unwrap();
+    }
+
+    fn phase(db: &Database) -> (FocusPhase, u32, bool) {
+        let timer = db.get_focus_timer().unwrap();
+        (timer.phase, timer.round, timer.is_running())
+    }
+
+    #[test]
+    fn cycles_through_the_phases() {
+        let db = Database::open_in_memory().unwrap();
+        db.set_setting("focus_long_break_every", "2").unwrap();
+
+        assert_eq!(phase(&db), (FocusPhase::Work, 1, false));
+        assert_eq!(db.skip_focus().unwrap(), FocusPhase::ShortBreak);
+        assert_eq!(db.skip_focus().unwrap(), FocusPhase::Work);
+        assert_eq!(phase(&db), (FocusPhase::Work, 2, false));
+        assert_eq!(db.skip_focus().unwrap(), FocusPhase::LongBreak);
+        assert_eq!(db.skip_focus().unwrap(), FocusPhase::Work);
+        assert_eq!(phase(&db), (FocusPhase::Work, 1, false));
+
+        // A running timer keeps running into the next phase
+        db.start_focus(None).unwrap();
+        db.skip_focus().unwrap();
+        assert_eq!(phase(&db), (FocusPhase::ShortBreak, 1, true));
+    }
+
+    #[test]
+    fn pausing_keeps_the_remaining_time() {
+        let db = Database::open_in_memory().unwrap();
+        let work = db.focus_config().unwrap().work_sec;
+        db.start_focus(None).unwrap();
+        run_for(&db, 60);
+
+        db.pause_focus().unwrap();
+        let paused = db.get_focus_timer().unwrap();
+        assert!(!paused.is_running());
+        assert!((work - 61..=work - 60).contains(&paused.remaining()));
+
+        db.start_focus(None).unwrap();
+        let resumed = db.get_focus_timer().unwrap();
+        assert!(resumed.is_running());
+        assert_eq!(resumed.remaining_sec, paused.remaining_sec);
+        assert_eq!(resumed.session_start, paused.session_start);
+    }
+
+    #[test]
+    fn a_finished_phase_is_claimed_once() {
+        let db = Database::open_in_memory().unwrap();
+        db.start_focus(None).unwrap();
+        assert_eq!(db.tick_focus().unwrap(), None);
+
+        let work = db.focus_config().unwrap().work_sec;
+        run_for(&db, work);
+        assert_eq!(db.tick_focus().unwrap(), Some(FocusPhase::Work));
+        assert_eq!(db.tick_focus().unwrap(), None);
+        assert_eq!(phase(&db), (FocusPhase::ShortBreak, 1, true));
+
+        // The notifier reads the same finished break, but the TUI claims it first
+        let short = db.focus_config().unwrap().short_break_sec;
+        run_for(&db, short);
+        let stale = db.get_focus_timer().unwrap();
+        assert_eq!(db.tick_focus().unwrap(), Some(FocusPhase::ShortBreak));
+        let claimed = db
+            .conn
+            .execute(
+                "UPDATE focus_timer SET running_since = NULL, remaining_sec = 0
+                 WHERE id = 1 AND running_since = ?1",
+                params![stale.running_since.map(|d| d.to_rfc3339())],
+            )
+            .unwrap();
+        assert_eq!(claimed, 0);
+    }
+
+    #[test]
+    fn a_completed_session_awards_the_bonus_outside_undo() {
+        let db = Database::open_in_memory().unwrap();
+        let task = Task::new(
+            "Deep work".into(),
+            String::new(),
+            TaskPriority::Medium,
+            10,
+            None,
+        );
+        db.create_task(&task).unwrap();
+
+        db.start_focus(Some(&task.id)).unwrap();
+        run_for(&db, 60);
+        db.skip_focus().unwrap();
+        assert_eq!(db.get_user_profile().unwrap().current_xp, 0);
+        assert!(db.get_pomodoro_counts().unwrap().is_empty());
+
+        db.skip_focus().unwrap();
+        db.start_focus(None).unwrap();
+        run_for(&db, db.focus_config().unwrap().work_sec);
+        db.tick_focus().unwrap();
+        assert_eq!(db.get_user_profile().unwrap().current_xp, FOCUS_BONUS_XP);
+        assert_eq!(db.get_pomodoro_counts().unwrap()[&task.id], 1);
+
+        assert_eq!(db.undo().unwrap(), None);
+        assert_eq!(db.get_user_profile().unwrap().current_xp, FOCUS_BONUS_XP);
+    }
+}
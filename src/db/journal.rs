@@ -0,0 +1,477 @@
+use crate::db::models::{Subtask, Task};
+use crate::db::{task_from_row, Database, TASK_COLUMNS};
+use chrono::Utc;
+use color_eyre::eyre::Result;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How many operations are kept for undo.
+const JOURNAL_LIMIT: i64 = 200;
+
+/// The tables a `Task` is read from, with the column that names its task.
+const TASK_TABLES: [(&str, &str); 4] = [
+    ("tasks", "id"),
+    ("subtasks", "task_id"),
+    ("task_tags", "task_id"),
+    ("task_dependencies", "task_id"),
+];
+
+/// What one operation changed: every touched task before and after (`None`
+/// means the task didn't exist), plus the XP it earned and any settings an
+/// import brought along. XP is kept as a delta so undo doesn't also take back
+/// what was earned since, like a focus bonus.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Changes {
+    tasks: Vec<TaskChange>,
+    #[serde(default)]
+    xp: i64,
+    #[serde(default)]
+    settings: Vec<SettingChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TaskChange {
+    before: Option<Task>,
+    after: Option<Task>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SettingChange {
+    key: String,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+impl Database {
+    /// Runs `f` in a transaction and journals whatever it changed under `label`
+    /// so it can be undone. Recording a new operation drops the redo history.
+    pub fn record<T>(&self, label: &str, f: impl FnOnce(&Self) -> Result<T>) -> Result<T> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.track_touched_tasks()?;
+        let xp_before = self.get_user_profile()?.total_xp();
+        let settings_before: HashMap<String, String> =
+            self.get_all_settings()?.into_iter().collect();
+
+        let result = f(self)?;
+
+        let tasks_before = self.touched_tasks("journal_")?;
+        let mut tasks_after = self.touched_tasks("main.")?;
+        self.untrack_touched_tasks()?;
+        let xp_after = self.get_user_profile()?.total_xp();
+        let mut settings_after: HashMap<String, String> =
+            self.get_all_settings()?.into_iter().collect();
+
+        let mut changes = Changes::default();
+        for (id, before) in tasks_before {
+            let after = tasks_after.remove(&id);
+            if after.as_ref() != Some(&before) {
+                changes.tasks.push(TaskChange {
+                    before: Some(before),
+                    after,
+                });
+            }
+        }
+        changes
+            .tasks
+            .extend(tasks_after.into_values().map(|after| TaskChange {
+                before: None,
+                after: Some(after),
+            }));
+        changes.xp = xp_after - xp_before;
+        for (key, before) in settings_before {
+            let after = settings_after.remove(&key);
+            if after.as_ref() != Some(&before) {
+                changes.settings.push(SettingChange {
+                    key,
+                    before: Some(before),
+                    after,
+                });
+            }
+        }
+        changes.settings.extend(
+            settings_after
+                .into_iter()
+                .map(|(key, after)| SettingChange {
+                    key,
+                    before: None,
+                    after: Some(after),
+                }),
+        );
+
+        if !changes.tasks.is_empty() || changes.xp != 0 || !changes.settings.is_empty() {
+            self.conn
+                .execute("DELETE FROM journal WHERE undone = 1", [])?;
+            self.conn.execute(
+                "INSERT INTO journal (label, changes, created_at) VALUES (?1, ?2, ?3)",
+                params![
+                    label,
+                    serde_json::to_string(&changes)?,
+                    Utc::now().to_rfc3339()
+                ],
+            )?;
+            self.conn.execute(
+                "DELETE FROM journal WHERE id <= (SELECT MAX(id) FROM journal) - ?1",
+                params![JOURNAL_LIMIT],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(result)
+    }
+
+    /// Reverts the latest operation, returning its label.
+    pub fn undo(&self) -> Result<Option<String>> {
+        self.step(false)
+    }
+
+    /// Re-applies the most recently undone operation, returning its label.
+    pub fn redo(&self) -> Result<Option<String>> {
+        self.step(true)
+    }
+
+    fn step(&self, redo: bool) -> Result<Option<String>> {
+        let query = if redo {
+            "SELECT id, label, changes FROM journal WHERE undone = 1 ORDER BY id ASC LIMIT 1"
+        } else {
+            "SELECT id, label, changes FROM journal WHERE undone = 0 ORDER BY id DESC LIMIT 1"
+        };
+        let mut stmt = self.conn.prepare(query)?;
+        let mut rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        let Some((id, label, changes)) = rows.next().transpose()? else {
+            return Ok(None);
+        };
+        let changes: Changes = serde_json::from_str(&changes)?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut restored = Vec::new();
+        for change in &changes.tasks {
+            let (from, to) = if redo {
+                (&change.before, &change.after)
+            } else {
+                (&change.after, &change.before)
+            };
+            match (from, to) {
+                (_, Some(task)) => restored.push(task.clone()),
+                (Some(task), None) => self.purge_task(&task.id)?,
+                (None, None) => {}
+            }
+        }
+        self.upsert_tasks(&restored)?;
+        if changes.xp != 0 {
+            let xp = if redo { changes.xp } else { -changes.xp };
+            self.add_xp(xp.clamp(i32::MIN as i64, i32::MAX as i64) as i32)?;
+        }
+        for change in &changes.settings {
+            let value = if redo { &change.after } else { &change.before };
+            match value {
+                Some(value) => self.set_setting(&change.key, value)?,
+                None => self.remove_setting(&change.key)?,
+            }
+        }
+        self.conn.execute(
+            "UPDATE journal SET undone = ?1 WHERE id = ?2",
+            params![!redo, id],
+        )?;
+        tx.commit()?;
+
+        Ok(Some(label))
+    }
+
+    /// Purges the task for good and drops it from the undo history, so undoing
+    /// or redoing an older operation can't bring it back.
+    pub fn erase_task(&self, id: &str) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.purge_task(id)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, changes FROM journal WHERE instr(changes, ?1) > 0")?;
+        let entries: Vec<(i64, String)> = stmt
+            .query_map(params![id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        for (entry, changes) in entries {
+            let mut changes: Changes = serde_json::from_str(&changes)?;
+            changes.tasks.retain(|change| {
+                change
+                    .before
+                    .as_ref()
+                    .or(change.after.as_ref())
+                    .is_none_or(|task| task.id != id)
+            });
+            for task in changes
+                .tasks
+                .iter_mut()
+                .flat_map(|change| [&mut change.before, &mut change.after])
+                .flatten()
+            {
+                task.blocked_by.retain(|blocker| blocker != id);
+            }
+
+            if changes.tasks.is_empty() && changes.xp == 0 && changes.settings.is_empty() {
+                self.conn
+                    .execute("DELETE FROM journal WHERE id = ?1", params![entry])?;
+            } else {
+                self.conn.execute(
+                    "UPDATE journal SET changes = ?1 WHERE id = ?2",
+                    params![serde_json::to_string(&changes)?, entry],
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Sets up temp triggers that copy a task's rows into `journal_*` tables
+    /// the first time the operation writes to it, so `record` only reads back
+    /// the tasks that were touched instead of snapshotting them all.
+    fn track_touched_tasks(&self) -> Result<()> {
+        let mut sql = String::from("CREATE TEMP TABLE journal_touched (id TEXT PRIMARY KEY);");
+        for (table, _) in TASK_TABLES {
+            sql += &format!(
+                "CREATE TEMP TABLE journal_{table} AS SELECT * FROM main.{table} WHERE 0;"
+            );
+        }
+        for (table, column) in TASK_TABLES {
+            for (event, row) in [("INSERT", "NEW"), ("UPDATE", "OLD"), ("DELETE", "OLD")] {
+                let id = format!("{row}.{column}");
+                sql += &format!(
+                    "CREATE TEMP TRIGGER journal_{table}_{event} BEFORE {event} ON main.{table}
+                     WHEN {id} NOT IN (SELECT id FROM journal_touched) BEGIN"
+                );
+                for (copy, key) in TASK_TABLES {
+                    sql += &format!(
+                        " INSERT INTO journal_{copy} SELECT * FROM main.{copy} WHERE {key} = {id};"
+                    );
+                }
+                sql += &format!(" INSERT INTO journal_touched VALUES ({id}); END;");
+            }
+        }
+        self.conn.execute_batch(&sql)?;
+        Ok(())
+    }
+
+    fn untrack_touched_tasks(&self) -> Result<()> {
+        let mut sql = String::from("DROP TABLE temp.journal_touched;");
+        for (table, _) in TASK_TABLES {
+            sql += &format!("DROP TABLE temp.journal_{table};");
+            for event in ["INSERT", "UPDATE", "DELETE"] {
+                sql += &format!("DROP TRIGGER temp.journal_{table}_{event};");
+            }
+        }
+        self.conn.execute_batch(&sql)?;
+        Ok(())
+    }
+
+    /// The touched tasks, read from the live tables (`prefix` "main.") or from
+    /// the copies taken before the operation wrote to them ("journal_").
+    fn touched_tasks(&self, prefix: &str) -> Result<HashMap<String, Task>> {
+        let touched = "IN (SELECT id FROM journal_touched)";
+        let mut stmt = self.conn.prepare(&format!(
+            "WITH tasks AS (SELECT * FROM {prefix}tasks),
+                  task_tags AS (SELECT * FROM {prefix}task_tags)
+             SELECT {TASK_COLUMNS} FROM tasks WHERE id {touched}"
+        ))?;
+        let mut tasks: HashMap<String, Task> = stmt
+            .query_map([], task_from_row)?
+            .map(|task| task.map(|t| (t.id.clone(), t)))
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, task_id, title, done FROM {prefix}subtasks WHERE task_id {touched} ORDER BY id"
+        ))?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            if let Some(task) = tasks.get_mut(&row.get::<_, String>(1)?) {
+                task.subtasks.push(Subtask {
+                    id: row.get(0)?,
+                    title: row.get(2)?,
+                    done: row.get(3)?,
+                });
+            }
+        }
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT task_id, blocked_by FROM {prefix}task_dependencies
+             WHERE task_id {touched} ORDER BY task_id, blocked_by"
+        ))?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            if let Some(task) = tasks.get_mut(&row.get::<_, String>(0)?) {
+                task.blocked_by.push(row.get(1)?);
+            }
+        }
+        Ok(tasks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::focus::FOCUS_BONUS_XP;
+    use crate::db::models::TaskPriority;
+
+    fn add(db: &Database, title: &str) -> Task {
+        let task = Task::new(title.into(), String::new(), TaskPriority::Medium, 10, None);
+        db.record("Add", |db| db.create_task(&task)).unwrap();
+        task
+    }
+
+    fn title(db: &Database, id: &str) -> Option<String> {
+        db.get_task(id).unwrap().map(|t| t.title)
+    }
+
+    #[test]
+    fn undoes_and_redoes_an_edit() {
+        let db = Database::open_in_memory().unwrap();
+        let task = add(&db, "Draft");
+        db.record("Labels", |db| {
+            db.add_subtask(&task.id, "Outline")?;
+            db.set_task_labels(&task.id, Some("blog"), &["writing".into()])
+        })
+        .unwrap();
+
+        db.record("Edit", |db| {
+            db.update_task_content(&task.id, "Publish", "", TaskPriority::High, 10, None, None)?;
+            let subtask = db.get_subtasks(&task.id)?[0].id;
+            db.set_subtask_done(subtask, true)?;
+            db.set_task_labels(&task.id, None, &[])
+        })
+        .unwrap();
+
+        assert_eq!(db.undo().unwrap().as_deref(), Some("Edit"));
+        let before = db.get_task(&task.id).unwrap().unwrap();
+        assert_eq!(before.title, "Draft");
+        assert_eq!(before.project.as_deref(), Some("blog"));
+        assert_eq!(before.tags, ["writing"]);
+        assert!(!before.subtasks[0].done);
+
+        assert_eq!(db.redo().unwrap().as_deref(), Some("Edit"));
+        let after = db.get_task(&task.id).unwrap().unwrap();
+        assert_eq!(after.title, "Publish");
+        assert_eq!(after.project, None);
+        assert!(after.tags.is_empty());
+        assert!(after.subtasks[0].done);
+        assert_eq!(db.redo().unwrap(), None);
+    }
+
+    #[test]
+    fn journals_only_the_touched_tasks() {
+        let db = Database::open_in_memory().unwrap();
+        let task = add(&db, "Touched");
+        add(&db, "Untouched");
+        db.record("Due", |db| db.set_due_date(&task.id, Some(Utc::now())))
+            .unwrap();
+
+        let changes: String = db
+            .conn
+            .query_row(
+                "SELECT changes FROM journal ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let changes: Changes = serde_json::from_str(&changes).unwrap();
+        assert_eq!(changes.tasks.len(), 1);
+        assert_eq!(changes.tasks[0].after.as_ref().unwrap().id, task.id);
+    }
+
+    #[test]
+    fn undoing_an_add_removes_the_tasks_and_redo_restores_them() {
+        let db = Database::open_in_memory().unwrap();
+        let first = Task::new(
+            "First".into(),
+            String::new(),
+            TaskPriority::Medium,
+            10,
+            None,
+        );
+        let mut second = Task::new(
+            "Second".into(),
+            String::new(),
+            TaskPriority::Medium,
+            10,
+            None,
+        );
+        second.blocked_by = vec![first.id.clone()];
+        db.record("Add both", |db| {
+            db.upsert_tasks(&[first.clone(), second.clone()])
+        })
+        .unwrap();
+
+        db.undo().unwrap();
+        assert_eq!(title(&db, &first.id), None);
+        assert_eq!(title(&db, &second.id), None);
+
+        db.redo().unwrap();
+        let second = db.get_task(&second.id).unwrap().unwrap();
+        assert_eq!(second.blocked_by, [first.id]);
+    }
+
+    #[test]
+    fn a_failed_operation_is_not_journaled() {
+        let db = Database::open_in_memory().unwrap();
+        let task = add(&db, "Keep");
+        let result: Result<()> = db.record("Fail", |db| {
+            db.update_task_content(&task.id, "Lost", "", TaskPriority::Low, 10, None, None)?;
+            color_eyre::eyre::bail!("nope")
+        });
+        assert!(result.is_err());
+        assert_eq!(title(&db, &task.id).as_deref(), Some("Keep"));
+
+        // The tracking tables went with the rollback, so the next operation works
+        db.record("Rename", |db| {
+            db.update_task_content(&task.id, "Renamed", "", TaskPriority::Low, 10, None, None)
+        })
+        .unwrap();
+        assert_eq!(db.undo().unwrap().as_deref(), Some("Rename"));
+    }
+
+    #[test]
+    fn erasing_a_task_drops_it_from_the_history() {
+        let db = Database::open_in_memory().unwrap();
+        let blocker = add(&db, "Blocker");
+        let task = add(&db, "Waiting");
+        db.record("Block", |db| db.add_dependency(&task.id, &blocker.id))
+            .unwrap();
+        db.record("Trash", |db| db.delete_task(&blocker.id))
+            .unwrap();
+
+        db.erase_task(&blocker.id).unwrap();
+        while db.undo().unwrap().is_some() {
+            assert_eq!(title(&db, &blocker.id), None);
+            if let Some(task) = db.get_task(&task.id).unwrap() {
+                assert!(task.blocked_by.is_empty());
+            }
+        }
+        while db.redo().unwrap().is_some() {
+            assert_eq!(title(&db, &blocker.id), None);
+        }
+        assert_eq!(title(&db, &task.id).as_deref(), Some("Waiting"));
+    }
+
+    #[test]
+    fn undoing_a_completion_keeps_xp_earned_since() {
+        let db = Database::open_in_memory().unwrap();
+        let task = add(&db, "Ship");
+        db.record("Complete", |db| db.complete_task(&task)).unwrap();
+        assert_eq!(db.get_user_profile().unwrap().current_xp, 10);
+
+        // A focus bonus lands outside the journal
+        db.add_xp(FOCUS_BONUS_XP).unwrap();
+
+        db.undo().unwrap();
+        let profile = db.get_user_profile().unwrap();
+        assert_eq!(profile.current_xp, FOCUS_BONUS_XP);
+        db.redo().unwrap();
+        let profile = db.get_user_profile().unwrap();
+        assert_eq!(profile.current_xp, 10 + FOCUS_BONUS_XP);
+    }
+}
@@ -0,0 +1,499 @@
+use color_eyre::eyre::{bail, Result};
+use rusqlite::Connection;
+
+/// A single schema change. `version` is what `PRAGMA user_version` is set to
+/// once `up` has run.
+struct Migration {
+    version: u32,
+    up: fn(&Connection) -> Result<()>,
+}
+
+/// Every migration the binary knows about, in application order.
+/// Append new entries at the end; never edit or reorder shipped ones.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        up: initial_schema,
+    },
+    Migration {
+        version: 2,
+        up: add_recurrence,
+    },
+    Migration {
+        version: 3,
+        up: add_projects_and_tags,
+    },
+    Migration {
+        version: 4,
+        up: add_subtasks,
+    },
+    Migration {
+        version: 5,
+        up: add_dependencies,
+    },
+    Migration {
+        version: 6,
+        up: add_positions,
+    },
+    Migration {
+        version: 7,
+        up: add_journal,
+    },
+    Migration {
+        version: 8,
+        up: add_trash,
+    },
+    Migration {
+        version: 9,
+        up: add_archive,
+    },
+    Migration {
+        version: 10,
+        up: add_time_entries,
+    },
+    Migration {
+        version: 11,
+        up: add_focus_sessions,
+    },
+    Migration {
+        version: 12,
+        up: add_focus_timer,
+    },
+    Migration {
+        version: 13,
+        up: add_reminders,
+    },
+    Migration {
+        version: 14,
+        up: add_external_ids,
+    },
+    Migration {
+        version: 15,
+        up: add_markdown_sync,
+    },
+    Migration {
+        version: 16,
+        up: add_task_commits,
+    },
+];
+
+/// Brings the database up to the latest schema version, one transaction per
+/// migration. Refuses to touch a database written by a newer binary.
+pub fn migrate(conn: &mut Connection) -> Result<()> {
+    let current = schema_version(conn)?;
+    let latest = MIGRATIONS.last().map(|m| m.version).unwrap_or(0);
+
+    if current > latest {
+        bail!(
+            "Database schema version {} is newer than this build supports ({}). Please upgrade zenith-cli.",
+            current,
+            latest
+        );
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.transaction()?;
+        (migration.up)(&tx)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+/// Whether the database is exactly at the version this build expects.
+pub fn is_current(conn: &Connection) -> Result<bool> {
+    Ok(schema_version(conn)? == MIGRATIONS.last().map(|m| m.version).unwrap_or(0))
+}
+
+fn schema_version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns: Vec<String> = stmt
+        .query_map([], |row| row.get(1))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(columns.iter().any(|c| c == column))
+}
+
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<()> {
+    if !has_column(conn, table, column)? {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+// --- MIGRATIONS ---
+
+/// v1: The 1.x schema. Also upgrades pre-versioning databases that predate
+/// the `priority` and `due_date` columns.
+fn initial_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tasks (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            description TEXT,
+            status TEXT DEFAULT 'TODO',
+            priority TEXT DEFAULT 'MEDIUM',
+            xp_reward INTEGER DEFAULT 10,
+            due_date TEXT,
+            created_at TEXT NOT NULL,
+            completed_at TEXT
+        )",
+        [],
+    )?;
+
+    add_column_if_missing(conn, "tasks", "priority", "TEXT DEFAULT 'MEDIUM'")?;
+    add_column_if_missing(conn, "tasks", "due_date", "TEXT")?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS user_profile (
+            id INTEGER PRIMARY KEY DEFAULT 1,
+            level INTEGER DEFAULT 1,
+            current_xp INTEGER DEFAULT 0,
+            next_level_xp INTEGER DEFAULT 100
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "INSERT OR IGNORE INTO user_profile (id, level, current_xp, next_level_xp) VALUES (1, 1, 0, 100)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// v2: RRULE-style repeat rule per task.
+fn add_recurrence(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE tasks ADD COLUMN recurrence TEXT", [])?;
+    Ok(())
+}
+
+/// v3: Projects (one per task) and tags (many per task).
+fn add_projects_and_tags(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE projects (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE
+        );
+        ALTER TABLE tasks ADD COLUMN project_id INTEGER REFERENCES projects(id);
+        CREATE TABLE tags (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE
+        );
+        CREATE TABLE task_tags (
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (task_id, tag_id)
+        );",
+    )?;
+    Ok(())
+}
+
+/// v4: Checklist items belonging to a task.
+fn add_subtasks(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE subtasks (
+            id INTEGER PRIMARY KEY,
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            title TEXT NOT NULL,
+            done INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// v5: "task_id is blocked by blocked_by" edges.
+fn add_dependencies(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE task_dependencies (
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            blocked_by TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            PRIMARY KEY (task_id, blocked_by)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// v6: Manual Kanban ordering. Existing tasks keep their newest-first order.
+fn add_positions(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE tasks ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+        UPDATE tasks SET position = (
+            SELECT COUNT(*) FROM tasks newer WHERE newer.created_at > tasks.created_at
+        );",
+    )?;
+    Ok(())
+}
+
+/// v7: Undo/redo journal. `changes` is JSON of the task rows before and after.
+fn add_journal(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE journal (
+            id INTEGER PRIMARY KEY,
+            label TEXT NOT NULL,
+            changes TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            undone INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// v8: Soft delete. Rows with `deleted_at` set are in the Trash.
+fn add_trash(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE tasks ADD COLUMN deleted_at TEXT", [])?;
+    Ok(())
+}
+
+/// v9: Archived tasks are kept for analytics but hidden from the active views.
+fn add_archive(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE tasks ADD COLUMN archived_at TEXT", [])?;
+    Ok(())
+}
+
+/// v10: Clocked time. An entry without `ended_at` is the running clock.
+fn add_time_entries(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE time_entries (
+            id INTEGER PRIMARY KEY,
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            started_at TEXT NOT NULL,
+            ended_at TEXT
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// v11: Pomodoro history. `task_id` is kept as NULL when the task is purged so
+/// the session still counts in the totals.
+fn add_focus_sessions(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE focus_sessions (
+            id INTEGER PRIMARY KEY,
+            task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL,
+            started_at TEXT NOT NULL,
+            planned_sec INTEGER NOT NULL,
+            actual_sec INTEGER NOT NULL,
+            completed INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// v12: The Pomodoro timer itself (a single row), so it survives restarts and
+/// can be driven from the CLI. `notifier_seen_at` is the notifier's heartbeat.
+fn add_focus_timer(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE focus_timer (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            phase TEXT NOT NULL,
+            round INTEGER NOT NULL,
+            duration_sec INTEGER NOT NULL,
+            remaining_sec INTEGER NOT NULL,
+            running_since TEXT,
+            session_start TEXT,
+            session_task TEXT REFERENCES tasks(id) ON DELETE SET NULL,
+            clocked_task TEXT REFERENCES tasks(id) ON DELETE SET NULL,
+            notifier_seen_at TEXT
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// v13: Due-date reminders. `reminder_log` remembers which reminder of which
+/// due date has been sent, so changing the due date re-arms them.
+fn add_reminders(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE reminder_log (
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            due_date TEXT NOT NULL,
+            event TEXT NOT NULL,
+            sent_at TEXT NOT NULL,
+            PRIMARY KEY (task_id, due_date, event)
+        );
+        CREATE TABLE snoozes (
+            task_id TEXT PRIMARY KEY REFERENCES tasks(id) ON DELETE CASCADE,
+            until TEXT NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+/// v14: Ids other tools use for our tasks (an iCalendar UID, ...), so
+/// importing the same file again updates instead of duplicating.
+fn add_external_ids(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE external_ids (
+            source TEXT NOT NULL,
+            external_id TEXT NOT NULL,
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            PRIMARY KEY (source, external_id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// v15: What each synced Markdown checklist line looked like after the last
+/// `sync-md`, so the next run can tell which side changed.
+fn add_markdown_sync(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE markdown_sync (
+            path TEXT NOT NULL,
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            text TEXT NOT NULL,
+            done INTEGER NOT NULL,
+            PRIMARY KEY (path, task_id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// v16: Git commits that mention a task, recorded by the post-commit hook.
+fn add_task_commits(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE task_commits (
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            hash TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            repo TEXT NOT NULL,
+            committed_at TEXT NOT NULL,
+            PRIMARY KEY (task_id, hash)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    fn latest() -> u32 {
+        MIGRATIONS.last().unwrap().version
+    }
+
+    #[test]
+    fn versions_are_in_order() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, i + 1);
+        }
+    }
+
+    #[test]
+    fn migrates_a_fresh_database() {
+        let mut db = Database::open_in_memory().unwrap();
+        assert_eq!(schema_version(&db.conn).unwrap(), latest());
+        assert!(is_current(&db.conn).unwrap());
+
+        let tables: Vec<String> = db
+            .conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        for table in [
+            "tasks",
+            "settings",
+            "user_profile",
+            "projects",
+            "tags",
+            "task_tags",
+            "subtasks",
+            "task_dependencies",
+            "journal",
+            "time_entries",
+            "focus_sessions",
+            "focus_timer",
+            "reminder_log",
+            "snoozes",
+            "external_ids",
+            "markdown_sync",
+            "task_commits",
+        ] {
+            assert!(tables.iter().any(|t| t == table), "missing {}", table);
+        }
+        for column in [
+            "recurrence",
+            "project_id",
+            "position",
+            "deleted_at",
+            "archived_at",
+        ] {
+            assert!(has_column(&db.conn, "tasks", column).unwrap(), "{}", column);
+        }
+
+        // Nothing left to do the second time
+        migrate(&mut db.conn).unwrap();
+        assert_eq!(schema_version(&db.conn).unwrap(), latest());
+    }
+
+    #[test]
+    fn upgrades_a_pre_versioning_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE tasks (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                description TEXT,
+                status TEXT DEFAULT 'TODO',
+                xp_reward INTEGER DEFAULT 10,
+                created_at TEXT NOT NULL,
+                completed_at TEXT
+            );
+            INSERT INTO tasks (id, title, created_at) VALUES ('old', 'Old task', '2024-01-01T00:00:00+00:00');",
+        )
+        .unwrap();
+
+        migrate(&mut conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), latest());
+        let (title, priority): (String, String) = conn
+            .query_row(
+                "SELECT title, priority FROM tasks WHERE id = 'old'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((title.as_str(), priority.as_str()), ("Old task", "MEDIUM"));
+    }
+
+    #[test]
+    fn refuses_a_newer_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest() + 1)
+            .unwrap();
+        assert!(migrate(&mut conn).is_err());
+        assert!(!is_current(&conn).unwrap());
+    }
+}
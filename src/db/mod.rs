@@ -1,12 +1,85 @@
-use crate::db::models::{Task, TaskPriority, TaskStatus, UserProfile};
-use chrono::{DateTime, Utc};
-use color_eyre::eyre::Result;
+use crate::db::models::{
+    FocusSession, Recurrence, Subtask, Task, TaskCommit, TaskPriority, TaskStatus, TimeEntry,
+    UserProfile,
+};
+use crate::utils::timezone;
+use chrono::{DateTime, NaiveDate, Utc};
+use color_eyre::eyre::{bail, Result};
 use directories::ProjectDirs;
-use rusqlite::{params, Connection};
+use rusqlite::{params, types::Type, Connection, OpenFlags, OptionalExtension, Row};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 
+pub mod focus;
+mod journal;
+mod migration;
 pub mod models;
+pub mod reminders;
+
+/// Reads a timestamp as we store them (RFC 3339).
+fn parse_time(s: &str) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(e)))
+}
+
+/// Whether making `task_id` wait on `blocked_by` would close a loop, given
+/// what each task already waits on.
+pub fn creates_cycle(
+    task_id: &str,
+    blocked_by: &str,
+    mut blockers: impl FnMut(&str) -> Result<Vec<String>>,
+) -> Result<bool> {
+    // Walk everything `blocked_by` waits on; reaching `task_id` means a cycle.
+    let mut stack = vec![blocked_by.to_string()];
+    let mut seen = HashSet::new();
+    while let Some(id) = stack.pop() {
+        if id == task_id {
+            return Ok(true);
+        }
+        if seen.insert(id.clone()) {
+            stack.extend(blockers(&id)?);
+        }
+    }
+    Ok(false)
+}
+
+const TASK_COLUMNS: &str = "id, title, description, status, priority, xp_reward, due_date, created_at, completed_at, recurrence,
+    (SELECT name FROM projects WHERE projects.id = tasks.project_id),
+    (SELECT group_concat(tags.name, ',') FROM task_tags JOIN tags ON tags.id = task_tags.tag_id WHERE task_tags.task_id = tasks.id),
+    position, deleted_at, archived_at";
+
+fn task_from_row(row: &Row) -> rusqlite::Result<Task> {
+    let due_date_str: Option<String> = row.get(6)?;
+    let created_at_str: String = row.get(7)?;
+    let completed_at_str: Option<String> = row.get(8)?;
+    let tags_str: Option<String> = row.get(11)?;
+    let deleted_at_str: Option<String> = row.get(13)?;
+    let archived_at_str: Option<String> = row.get(14)?;
+
+    Ok(Task {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        status: row.get(3)?,
+        priority: row.get(4)?,
+        xp_reward: row.get(5)?,
+        due_date: due_date_str.map(|s| parse_time(&s)).transpose()?,
+        created_at: parse_time(&created_at_str)?,
+        completed_at: completed_at_str.map(|s| parse_time(&s)).transpose()?,
+        recurrence: row.get(9)?,
+        project: row.get(10)?,
+        tags: tags_str
+            .map(|s| s.split(',').map(|t| t.to_string()).collect())
+            .unwrap_or_default(),
+        subtasks: Vec::new(),
+        blocked_by: Vec::new(),
+        position: row.get(12)?,
+        deleted_at: deleted_at_str.map(|s| parse_time(&s)).transpose()?,
+        archived_at: archived_at_str.map(|s| parse_time(&s)).transpose()?,
+    })
+}
 
 pub struct Database {
     conn: Connection,
@@ -15,78 +88,57 @@ pub struct Database {
 impl Database {
     pub fn init() -> Result<Self> {
         let db_path = Self::get_db_path()?;
-        let conn = Connection::open(db_path)?;
-
-        // Create Tables
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS tasks (
-                id TEXT PRIMARY KEY,
-                title TEXT NOT NULL,
-                description TEXT,
-                status TEXT DEFAULT 'TODO',
-                priority TEXT DEFAULT 'MEDIUM',
-                xp_reward INTEGER DEFAULT 10,
-                due_date TEXT,
-                created_at TEXT NOT NULL,
-                completed_at TEXT
-            )",
-            [],
-        )?;
-
-        // Migration: Add priority if missing
-        let has_priority = {
-            let mut stmt = conn.prepare("PRAGMA table_info(tasks)")?;
-            let columns: Vec<String> = stmt
-                .query_map([], |row| row.get(1))?
-                .filter_map(|r| r.ok())
-                .collect();
-            columns.contains(&"priority".to_string())
-        };
+        let mut conn = Connection::open(db_path)?;
+        // The TUI, the CLI and the focus notifier may all have it open
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        // Purging a task relies on the schema's ON DELETE actions
+        conn.pragma_update(None, "foreign_keys", true)?;
+        migration::migrate(&mut conn)?;
 
-        if !has_priority {
-            conn.execute(
-                "ALTER TABLE tasks ADD COLUMN priority TEXT DEFAULT 'MEDIUM'",
-                [],
-            )?;
-        }
-
-        // Migration: Add due_date if missing
-        let has_due_date = {
-            let mut stmt = conn.prepare("PRAGMA table_info(tasks)")?;
-            let columns: Vec<String> = stmt
-                .query_map([], |row| row.get(1))?
-                .filter_map(|r| r.ok())
-                .collect();
-            columns.contains(&"due_date".to_string())
-        };
+        let db = Self { conn };
+        timezone::init(db.get_setting("timezone")?.as_deref());
+        db.purge_expired_trash()?;
+        db.auto_archive()?;
+        Ok(db)
+    }
 
-        if !has_due_date {
-            conn.execute("ALTER TABLE tasks ADD COLUMN due_date TEXT", [])?;
+    /// Opens the database read-only, skipping migrations and the startup
+    /// chores, for commands that are called on every prompt refresh. Falls
+    /// back to `init` when there is no database yet or its schema is behind.
+    pub fn open_read_only() -> Result<Self> {
+        match Self::open_existing_read_only()? {
+            Some(db) => Ok(db),
+            None => Self::init(),
         }
+    }
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS settings (
-                key TEXT PRIMARY KEY,
-                value TEXT
-            )",
-            [],
-        )?;
-
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS user_profile (
-                id INTEGER PRIMARY KEY DEFAULT 1,
-                level INTEGER DEFAULT 1,
-                current_xp INTEGER DEFAULT 0,
-                next_level_xp INTEGER DEFAULT 100
-            )",
-            [],
-        )?;
-
-        conn.execute(
-            "INSERT OR IGNORE INTO user_profile (id, level, current_xp, next_level_xp) VALUES (1, 1, 0, 100)",
-            [],
+    /// Like `open_read_only`, but `None` instead of falling back to `init`
+    /// when there is no database yet or its schema isn't the current one.
+    pub fn open_existing_read_only() -> Result<Option<Self>> {
+        let db_path = Self::get_db_path()?;
+        if !db_path.exists() {
+            return Ok(None);
+        }
+        let conn = Connection::open_with_flags(
+            &db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
         )?;
+        if !migration::is_current(&conn)? {
+            return Ok(None);
+        }
+        conn.busy_timeout(std::time::Duration::from_secs(1))?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        let db = Self { conn };
+        timezone::init(db.get_setting("timezone")?.as_deref());
+        Ok(Some(db))
+    }
 
+    /// A fresh, fully migrated database that lives only as long as the value.
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        let mut conn = Connection::open_in_memory()?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        migration::migrate(&mut conn)?;
         Ok(Self { conn })
     }
 
@@ -102,10 +154,11 @@ impl Database {
         }
     }
 
+    /// New tasks go to the top of their Kanban column.
     pub fn create_task(&self, task: &Task) -> Result<()> {
         self.conn.execute(
-            "INSERT INTO tasks (id, title, description, status, priority, xp_reward, due_date, created_at) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO tasks (id, title, description, status, priority, xp_reward, due_date, created_at, recurrence, position) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, (SELECT COALESCE(MIN(position), 0) - 1 FROM tasks))",
             params![
                 task.id,
                 task.title,
@@ -114,104 +167,811 @@ impl Database {
                 task.priority,
                 task.xp_reward,
                 task.due_date.map(|d| d.to_rfc3339()),
-                task.created_at.to_rfc3339()
+                task.created_at.to_rfc3339(),
+                task.recurrence
             ],
         )?;
+        self.replace_subtasks(task)?;
+        self.replace_dependencies(task)?;
+        self.set_task_labels(&task.id, task.project.as_deref(), &task.tags)
+    }
+
+    /// Inserts the tasks, or overwrites every column of existing tasks with the same id.
+    /// Every row is written before any dependency, so a task can wait on one that comes
+    /// later in `tasks`.
+    pub fn upsert_tasks(&self, tasks: &[Task]) -> Result<()> {
+        for task in tasks {
+            self.write_task_row(task)?;
+        }
+        for task in tasks {
+            self.replace_dependencies(task)?;
+        }
         Ok(())
     }
 
+    /// Updates in place rather than `INSERT OR REPLACE`, which would delete the row and
+    /// cascade away the dependencies of tasks it blocks.
+    fn write_task_row(&self, task: &Task) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO tasks (id, title, description, status, priority, xp_reward, due_date, created_at, completed_at, recurrence, position, deleted_at, archived_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+             ON CONFLICT(id) DO UPDATE SET title = excluded.title, description = excluded.description,
+                status = excluded.status, priority = excluded.priority, xp_reward = excluded.xp_reward,
+                due_date = excluded.due_date, created_at = excluded.created_at,
+                completed_at = excluded.completed_at, recurrence = excluded.recurrence,
+                position = excluded.position, deleted_at = excluded.deleted_at,
+                archived_at = excluded.archived_at",
+            params![
+                task.id,
+                task.title,
+                task.description,
+                task.status,
+                task.priority,
+                task.xp_reward,
+                task.due_date.map(|d| d.to_rfc3339()),
+                task.created_at.to_rfc3339(),
+                task.completed_at.map(|d| d.to_rfc3339()),
+                task.recurrence,
+                task.position,
+                task.deleted_at.map(|d| d.to_rfc3339()),
+                task.archived_at.map(|d| d.to_rfc3339())
+            ],
+        )?;
+        self.replace_subtasks(task)?;
+        self.set_task_labels(&task.id, task.project.as_deref(), &task.tags)
+    }
+
+    /// The active tasks (not archived or in the Trash), newest first.
     pub fn get_all_tasks(&self) -> Result<Vec<Task>> {
+        self.query_tasks(
+            "WHERE deleted_at IS NULL AND archived_at IS NULL ORDER BY created_at DESC",
+        )
+    }
+
+    /// Archived tasks, most recently completed first.
+    pub fn get_archived_tasks(&self) -> Result<Vec<Task>> {
+        self.query_tasks(
+            "WHERE deleted_at IS NULL AND archived_at IS NOT NULL ORDER BY completed_at DESC",
+        )
+    }
+
+    /// Active and archived tasks; what a backup should contain.
+    pub fn get_exportable_tasks(&self) -> Result<Vec<Task>> {
+        self.query_tasks("WHERE deleted_at IS NULL ORDER BY created_at DESC")
+    }
+
+    /// Every stored row, including archived and trashed tasks.
+    pub fn get_every_task(&self) -> Result<Vec<Task>> {
+        self.query_tasks("ORDER BY created_at DESC")
+    }
+
+    /// The first card of the Kanban DOING column, if any.
+    pub fn get_doing_task(&self) -> Result<Option<Task>> {
+        Ok(self
+            .query_tasks(
+                "WHERE status = 'DOING' AND deleted_at IS NULL AND archived_at IS NULL
+                 ORDER BY position LIMIT 1",
+            )?
+            .pop())
+    }
+
+    /// Tasks in the Trash, most recently deleted first.
+    pub fn get_trashed_tasks(&self) -> Result<Vec<Task>> {
+        self.query_tasks("WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC")
+    }
+
+    /// Tasks outside the Trash that another task waits on, by id.
+    pub fn get_blocking_tasks(&self) -> Result<HashMap<String, Task>> {
+        Ok(self
+            .query_tasks(
+                "WHERE deleted_at IS NULL AND id IN (SELECT blocked_by FROM task_dependencies)",
+            )?
+            .into_iter()
+            .map(|t| (t.id.clone(), t))
+            .collect())
+    }
+
+    fn query_tasks(&self, clause: &str) -> Result<Vec<Task>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM tasks {}", TASK_COLUMNS, clause))?;
+
+        let task_iter = stmt.query_map([], task_from_row)?;
+
+        let mut subtasks = self.get_all_subtasks()?;
+        let mut blockers = self.get_all_dependencies()?;
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            let mut task = task?;
+            task.subtasks = subtasks.remove(&task.id).unwrap_or_default();
+            task.blocked_by = blockers.remove(&task.id).unwrap_or_default();
+            tasks.push(task);
+        }
+        Ok(tasks)
+    }
+
+    pub fn get_task(&self, id: &str) -> Result<Option<Task>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM tasks WHERE id = ?1", TASK_COLUMNS))?;
+        let mut rows = stmt.query_map(params![id], task_from_row)?;
+        let Some(mut task) = rows.next().transpose()? else {
+            return Ok(None);
+        };
+        task.subtasks = self.get_subtasks(id)?;
+        task.blocked_by = self.get_blockers(id)?;
+        Ok(Some(task))
+    }
+
+    fn get_all_dependencies(&self) -> Result<HashMap<String, Vec<String>>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT task_id, blocked_by FROM task_dependencies")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut by_task: HashMap<String, Vec<String>> = HashMap::new();
+        for r in rows {
+            let (task_id, blocker) = r?;
+            by_task.entry(task_id).or_default().push(blocker);
+        }
+        Ok(by_task)
+    }
+
+    fn get_blockers(&self, task_id: &str) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT blocked_by FROM task_dependencies WHERE task_id = ?1")?;
+        let rows = stmt.query_map(params![task_id], |row| row.get(0))?;
+
+        let mut blockers = Vec::new();
+        for r in rows {
+            blockers.push(r?);
+        }
+        Ok(blockers)
+    }
+
+    /// Ids of every task that still has at least one unfinished blocker.
+    pub fn get_blocked_task_ids(&self) -> Result<HashSet<String>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, description, status, priority, xp_reward, due_date, created_at, completed_at FROM tasks ORDER BY created_at DESC"
+            "SELECT DISTINCT d.task_id FROM task_dependencies d
+             JOIN tasks b ON b.id = d.blocked_by
+             WHERE b.status != 'DONE' AND b.deleted_at IS NULL",
         )?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+
+        let mut ids = HashSet::new();
+        for r in rows {
+            ids.insert(r?);
+        }
+        Ok(ids)
+    }
+
+    /// Records that `task_id` can't start until `blocked_by` is done.
+    /// Refuses edges that would make a task (transitively) wait on itself.
+    pub fn add_dependency(&self, task_id: &str, blocked_by: &str) -> Result<()> {
+        if task_id == blocked_by {
+            bail!("A task can't block itself");
+        }
+        if creates_cycle(task_id, blocked_by, |id| self.get_blockers(id))? {
+            bail!("That dependency would create a cycle");
+        }
 
-        let task_iter = stmt.query_map([], |row| {
-            let due_date_str: Option<String> = row.get(6)?;
-            let created_at_str: String = row.get(7)?;
-            let completed_at_str: Option<String> = row.get(8)?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO task_dependencies (task_id, blocked_by) VALUES (?1, ?2)",
+            params![task_id, blocked_by],
+        )?;
+        Ok(())
+    }
 
-            Ok(Task {
+    pub fn remove_dependency(&self, task_id: &str, blocked_by: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM task_dependencies WHERE task_id = ?1 AND blocked_by = ?2",
+            params![task_id, blocked_by],
+        )?;
+        Ok(())
+    }
+
+    fn get_all_subtasks(&self) -> Result<HashMap<String, Vec<Subtask>>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, task_id, title, done FROM subtasks ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(1)?,
+                Subtask {
+                    id: row.get(0)?,
+                    title: row.get(2)?,
+                    done: row.get(3)?,
+                },
+            ))
+        })?;
+
+        let mut by_task: HashMap<String, Vec<Subtask>> = HashMap::new();
+        for r in rows {
+            let (task_id, subtask) = r?;
+            by_task.entry(task_id).or_default().push(subtask);
+        }
+        Ok(by_task)
+    }
+
+    pub fn get_subtasks(&self, task_id: &str) -> Result<Vec<Subtask>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, title, done FROM subtasks WHERE task_id = ?1 ORDER BY id")?;
+        let rows = stmt.query_map(params![task_id], |row| {
+            Ok(Subtask {
                 id: row.get(0)?,
                 title: row.get(1)?,
-                description: row.get(2)?,
-                status: row.get(3)?,
-                priority: row.get(4)?,
-                xp_reward: row.get(5)?,
-                due_date: due_date_str.map(|s| {
-                    DateTime::parse_from_rfc3339(&s)
-                        .unwrap()
-                        .with_timezone(&Utc)
-                }),
-                created_at: DateTime::parse_from_rfc3339(&created_at_str)
-                    .unwrap()
-                    .with_timezone(&Utc),
-                completed_at: completed_at_str.map(|s| {
-                    DateTime::parse_from_rfc3339(&s)
-                        .unwrap()
-                        .with_timezone(&Utc)
-                }),
+                done: row.get(2)?,
             })
         })?;
 
-        let mut tasks = Vec::new();
-        for task in task_iter {
-            tasks.push(task?);
+        let mut subtasks = Vec::new();
+        for r in rows {
+            subtasks.push(r?);
         }
-        Ok(tasks)
+        Ok(subtasks)
+    }
+
+    fn replace_subtasks(&self, task: &Task) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM subtasks WHERE task_id = ?1", params![task.id])?;
+        for subtask in &task.subtasks {
+            self.conn.execute(
+                "INSERT INTO subtasks (task_id, title, done) VALUES (?1, ?2, ?3)",
+                params![task.id, subtask.title, subtask.done],
+            )?;
+        }
+        Ok(())
     }
 
+    fn replace_dependencies(&self, task: &Task) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM task_dependencies WHERE task_id = ?1",
+            params![task.id],
+        )?;
+        for blocker in &task.blocked_by {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO task_dependencies (task_id, blocked_by) VALUES (?1, ?2)",
+                params![task.id, blocker],
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn add_subtask(&self, task_id: &str, title: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO subtasks (task_id, title) VALUES (?1, ?2)",
+            params![task_id, title],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_subtask_done(&self, id: i64, done: bool) -> Result<()> {
+        self.conn.execute(
+            "UPDATE subtasks SET done = ?1 WHERE id = ?2",
+            params![done, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_subtask(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM subtasks WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Completing a recurring task hands its repeat rule over to a freshly
+    /// created next occurrence, unless the series has run past year 9999.
     pub fn update_task_status(&self, id: &str, status: TaskStatus) -> Result<()> {
+        let now = Utc::now();
         let completed_at = if status == TaskStatus::Done {
-            Some(Utc::now().to_rfc3339())
+            Some(now.to_rfc3339())
         } else {
             None
         };
 
+        let recurring = match self.get_task(id)? {
+            Some(task) if status == TaskStatus::Done && task.status != TaskStatus::Done => {
+                task.recurrence.is_some().then_some(task)
+            }
+            _ => None,
+        };
+
         self.conn.execute(
             "UPDATE tasks SET status = ?1, completed_at = ?2 WHERE id = ?3",
             params![status, completed_at, id],
         )?;
+        if status == TaskStatus::Done {
+            self.stop_clock_on(id)?;
+        }
+
+        let next_due = recurring.as_ref().and_then(|task| {
+            let rule = task.recurrence.as_ref()?;
+            rule.next_due(task.due_date, now)
+        });
+        if let (Some(task), Some(due)) = (recurring, next_due) {
+            let rule = task.recurrence.clone().unwrap();
+            let mut next = Task::new(
+                task.title,
+                task.description,
+                task.priority,
+                task.xp_reward,
+                Some(due),
+            );
+            next.recurrence = Some(rule);
+            next.project = task.project;
+            next.tags = task.tags;
+            next.subtasks = task
+                .subtasks
+                .into_iter()
+                .map(|s| Subtask { done: false, ..s })
+                .collect();
+            self.create_task(&next)?;
+            self.conn.execute(
+                "UPDATE tasks SET recurrence = NULL WHERE id = ?1",
+                params![id],
+            )?;
+        }
         Ok(())
     }
 
+    /// Marks the task done and awards its XP. Returns `false`, changing
+    /// nothing, while an unfinished task still blocks it.
+    pub fn complete_task(&self, task: &Task) -> Result<bool> {
+        if self.get_blocked_task_ids()?.contains(&task.id) {
+            return Ok(false);
+        }
+        if task.status != TaskStatus::Done {
+            self.add_xp(task.xp_reward)?;
+            self.update_task_status(&task.id, TaskStatus::Done)?;
+        }
+        Ok(true)
+    }
+
+    /// Stores `ids` as the manual order: the first id gets the lowest position.
+    pub fn set_positions(&self, ids: &[String]) -> Result<()> {
+        for (position, id) in ids.iter().enumerate() {
+            self.conn.execute(
+                "UPDATE tasks SET position = ?1 WHERE id = ?2",
+                params![position as i64, id],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Moves the task to the Trash. It keeps its checklist, labels and links
+    /// so a restore brings it back exactly as it was.
     pub fn delete_task(&self, id: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE tasks SET deleted_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+        self.stop_clock_on(id)
+    }
+
+    pub fn archive_task(&self, id: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE tasks SET archived_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+        self.stop_clock_on(id)
+    }
+
+    pub fn unarchive_task(&self, id: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE tasks SET archived_at = NULL WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    /// Archives tasks that have been done for longer than `auto_archive_days`
+    /// (default 7, `0` turns it off). Only completions that crossed the cutoff
+    /// since the last run are archived, so an unarchived task stays put.
+    fn auto_archive(&self) -> Result<()> {
+        let days = self
+            .get_setting("auto_archive_days")?
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(7);
+        if days <= 0 {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        let cutoff = (now - chrono::Duration::days(days)).to_rfc3339();
+        let previous = self.get_setting("auto_archived_until")?.unwrap_or_default();
+        self.conn.execute(
+            "UPDATE tasks SET archived_at = ?1
+             WHERE status = 'DONE' AND archived_at IS NULL
+               AND completed_at >= ?2 AND completed_at < ?3",
+            params![now.to_rfc3339(), previous, cutoff],
+        )?;
+        self.set_setting("auto_archived_until", &cutoff)
+    }
+
+    pub fn restore_task(&self, id: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE tasks SET deleted_at = NULL WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    /// Permanently removes the task and everything attached to it.
+    pub fn purge_task(&self, id: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM task_tags WHERE task_id = ?1", params![id])?;
+        self.conn
+            .execute("DELETE FROM subtasks WHERE task_id = ?1", params![id])?;
+        self.conn.execute(
+            "DELETE FROM task_dependencies WHERE task_id = ?1 OR blocked_by = ?1",
+            params![id],
+        )?;
+        self.conn
+            .execute("DELETE FROM time_entries WHERE task_id = ?1", params![id])?;
+        self.conn.execute(
+            "UPDATE focus_sessions SET task_id = NULL WHERE task_id = ?1",
+            params![id],
+        )?;
+        // Reminders, snoozes, external ids, sync state, linked commits and the
+        // focus timer follow through their ON DELETE actions
         self.conn
             .execute("DELETE FROM tasks WHERE id = ?1", params![id])?;
         Ok(())
     }
 
+    /// How long deleted tasks stay in the Trash (`trash_retention_days`,
+    /// default 30). `0` keeps them forever.
+    pub fn trash_retention_days(&self) -> Result<i64> {
+        Ok(self
+            .get_setting("trash_retention_days")?
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(30))
+    }
+
+    fn purge_expired_trash(&self) -> Result<()> {
+        let days = self.trash_retention_days()?;
+        if days <= 0 {
+            return Ok(());
+        }
+
+        let cutoff = (Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM tasks WHERE deleted_at IS NOT NULL AND deleted_at < ?1")?;
+        let ids: Vec<String> = stmt
+            .query_map(params![cutoff], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        for id in ids {
+            self.erase_task(&id)?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn update_task_content(
         &self,
         id: &str,
         title: &str,
         description: &str,
         priority: TaskPriority,
+        xp_reward: i32,
         due_date: Option<DateTime<Utc>>,
+        recurrence: Option<&Recurrence>,
     ) -> Result<()> {
         self.conn.execute(
-            "UPDATE tasks SET title = ?1, description = ?2, priority = ?3, due_date = ?4 WHERE id = ?5",
-            params![title, description, priority, due_date.map(|d| d.to_rfc3339()), id],
+            "UPDATE tasks SET title = ?1, description = ?2, priority = ?3, xp_reward = ?4, due_date = ?5, recurrence = ?6 WHERE id = ?7",
+            params![title, description, priority, xp_reward, due_date.map(|d| d.to_rfc3339()), recurrence, id],
         )?;
         Ok(())
     }
 
-    pub fn get_weekly_stats(&self) -> Result<Vec<(String, u64)>> {
+    /// The task another tool knows as `external_id`.
+    pub fn find_external_id(&self, source: &str, external_id: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT task_id FROM external_ids WHERE source = ?1 AND external_id = ?2",
+                params![source, external_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    pub fn set_external_id(&self, source: &str, external_id: &str, task_id: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO external_ids (source, external_id, task_id) VALUES (?1, ?2, ?3)",
+            params![source, external_id, task_id],
+        )?;
+        Ok(())
+    }
+
+    /// Task id -> external id for one source.
+    pub fn get_external_ids(&self, source: &str) -> Result<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT task_id, external_id FROM external_ids WHERE source = ?1")?;
+        let rows = stmt.query_map(params![source], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut ids = HashMap::new();
+        for r in rows {
+            let (task_id, external_id) = r?;
+            ids.insert(task_id, external_id);
+        }
+        Ok(ids)
+    }
+
+    /// Task id -> (line text, checked) as of the last `sync-md` of `path`.
+    pub fn get_markdown_sync(&self, path: &str) -> Result<HashMap<String, (String, bool)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT task_id, text, done FROM markdown_sync WHERE path = ?1")?;
+        let rows = stmt.query_map(params![path], |row| {
+            Ok((row.get(0)?, (row.get(1)?, row.get(2)?)))
+        })?;
+
+        let mut lines = HashMap::new();
+        for r in rows {
+            let (task_id, line) = r?;
+            lines.insert(task_id, line);
+        }
+        Ok(lines)
+    }
+
+    pub fn set_markdown_sync(
+        &self,
+        path: &str,
+        task_id: &str,
+        text: &str,
+        done: bool,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO markdown_sync (path, task_id, text, done) VALUES (?1, ?2, ?3, ?4)",
+            params![path, task_id, text, done],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_markdown_sync(&self, path: &str, task_id: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM markdown_sync WHERE path = ?1 AND task_id = ?2",
+            params![path, task_id],
+        )?;
+        Ok(())
+    }
+
+    /// Links `commit` to the task; linking the same commit again does nothing.
+    pub fn add_task_commit(&self, task_id: &str, commit: &TaskCommit) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO task_commits (task_id, hash, summary, repo, committed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                task_id,
+                commit.hash,
+                commit.summary,
+                commit.repo,
+                commit.committed_at.to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Linked commits per task, newest first.
+    pub fn get_task_commits(&self) -> Result<HashMap<String, Vec<TaskCommit>>> {
         let mut stmt = self.conn.prepare(
-            "SELECT substr(completed_at, 1, 10) as day, COUNT(*) 
-             FROM tasks 
-             WHERE status = 'Done' AND completed_at IS NOT NULL
-             GROUP BY day 
-             ORDER BY day DESC 
-             LIMIT 7",
+            "SELECT task_id, hash, summary, repo, committed_at FROM task_commits
+             ORDER BY committed_at DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let task_id: String = row.get(0)?;
+            let commit = TaskCommit {
+                hash: row.get(1)?,
+                summary: row.get(2)?,
+                repo: row.get(3)?,
+                committed_at: parse_time(&row.get::<_, String>(4)?)?,
+            };
+            Ok((task_id, commit))
+        })?;
+
+        let mut commits: HashMap<String, Vec<TaskCommit>> = HashMap::new();
+        for r in rows {
+            let (task_id, commit) = r?;
+            commits.entry(task_id).or_default().push(commit);
+        }
+        Ok(commits)
+    }
+
+    pub fn set_due_date(&self, id: &str, due_date: Option<DateTime<Utc>>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE tasks SET due_date = ?1 WHERE id = ?2",
+            params![due_date.map(|d| d.to_rfc3339()), id],
+        )?;
+        Ok(())
+    }
+
+    /// Replaces the task's project and tags, creating any that don't exist yet.
+    pub fn set_task_labels(&self, id: &str, project: Option<&str>, tags: &[String]) -> Result<()> {
+        let project_id: Option<i64> = match project {
+            Some(name) => {
+                self.conn.execute(
+                    "INSERT OR IGNORE INTO projects (name) VALUES (?1)",
+                    params![name],
+                )?;
+                Some(self.conn.query_row(
+                    "SELECT id FROM projects WHERE name = ?1",
+                    params![name],
+                    |row| row.get(0),
+                )?)
+            }
+            None => None,
+        };
+        self.conn.execute(
+            "UPDATE tasks SET project_id = ?1 WHERE id = ?2",
+            params![project_id, id],
+        )?;
+
+        self.conn
+            .execute("DELETE FROM task_tags WHERE task_id = ?1", params![id])?;
+        for tag in tags {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO tags (name) VALUES (?1)",
+                params![tag],
+            )?;
+            self.conn.execute(
+                "INSERT OR IGNORE INTO task_tags (task_id, tag_id)
+                 SELECT ?1, id FROM tags WHERE name = ?2",
+                params![id, tag],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Clocks in on `task_id`. Only one clock runs at a time, so any running
+    /// entry is stopped first.
+    pub fn start_clock(&self, task_id: &str) -> Result<()> {
+        self.stop_clock()?;
+        self.conn.execute(
+            "INSERT INTO time_entries (task_id, started_at) VALUES (?1, ?2)",
+            params![task_id, Utc::now().to_rfc3339()],
         )?;
+        Ok(())
+    }
 
+    pub fn stop_clock(&self) -> Result<()> {
+        self.conn.execute(
+            "UPDATE time_entries SET ended_at = ?1 WHERE ended_at IS NULL",
+            params![Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Clocks out if the running clock is on `task_id`, for a task that's
+    /// been completed, trashed or archived.
+    fn stop_clock_on(&self, task_id: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE time_entries SET ended_at = ?1 WHERE ended_at IS NULL AND task_id = ?2",
+            params![Utc::now().to_rfc3339(), task_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn running_clock(&self) -> Result<Option<TimeEntry>> {
+        Ok(self
+            .query_time_entries("WHERE ended_at IS NULL", [])?
+            .into_iter()
+            .next())
+    }
+
+    /// Entries that started at or after `since`, oldest first.
+    pub fn get_time_entries(&self, since: DateTime<Utc>) -> Result<Vec<TimeEntry>> {
+        self.query_time_entries(
+            "WHERE started_at >= ?1 ORDER BY started_at",
+            params![since.to_rfc3339()],
+        )
+    }
+
+    /// Seconds of finished entries per task. The running clock isn't included.
+    pub fn get_time_totals(&self) -> Result<HashMap<String, i64>> {
+        let mut totals = HashMap::new();
+        for entry in self.query_time_entries("WHERE ended_at IS NOT NULL", [])? {
+            *totals.entry(entry.task_id.clone()).or_insert(0) += entry.seconds();
+        }
+        Ok(totals)
+    }
+
+    pub fn add_focus_session(&self, session: &FocusSession) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO focus_sessions (task_id, started_at, planned_sec, actual_sec, completed)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                session.task_id,
+                session.started_at.to_rfc3339(),
+                session.planned_sec,
+                session.actual_sec,
+                session.completed
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Completed Pomodoros per task.
+    pub fn get_pomodoro_counts(&self) -> Result<HashMap<String, u32>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT task_id, COUNT(*) FROM focus_sessions
+             WHERE completed = 1 AND task_id IS NOT NULL
+             GROUP BY task_id",
+        )?;
         let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
 
-        let mut stats = Vec::new();
+        let mut counts = HashMap::new();
+        for r in rows {
+            let (id, count) = r?;
+            counts.insert(id, count);
+        }
+        Ok(counts)
+    }
+
+    fn query_time_entries(
+        &self,
+        clause: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<TimeEntry>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT task_id, started_at, ended_at FROM time_entries {}",
+            clause
+        ))?;
+        let rows = stmt.query_map(params, |row| {
+            let started_at: String = row.get(1)?;
+            let ended_at: Option<String> = row.get(2)?;
+            Ok(TimeEntry {
+                task_id: row.get(0)?,
+                started_at: parse_time(&started_at)?,
+                ended_at: ended_at.map(|s| parse_time(&s)).transpose()?,
+            })
+        })?;
+
+        let mut entries = Vec::new();
         for r in rows {
-            stats.push(r?);
+            entries.push(r?);
+        }
+        Ok(entries)
+    }
+
+    /// Local day of every completion. Archived tasks still count; only
+    /// trashed ones are left out.
+    fn completion_days(&self) -> Result<Vec<NaiveDate>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT completed_at FROM tasks
+             WHERE status = 'DONE' AND completed_at IS NOT NULL AND deleted_at IS NULL",
+        )?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut days = Vec::new();
+        for r in rows {
+            if let Ok(completed_at) = DateTime::parse_from_rfc3339(&r?) {
+                days.push(timezone::local(completed_at.with_timezone(&Utc)).date());
+            }
+        }
+        Ok(days)
+    }
+
+    /// Completions per day for the last 7 active days, newest first.
+    pub fn get_weekly_stats(&self) -> Result<Vec<(String, u64)>> {
+        let mut counts: HashMap<NaiveDate, u64> = HashMap::new();
+        for day in self.completion_days()? {
+            *counts.entry(day).or_default() += 1;
         }
-        Ok(stats)
+
+        let mut stats: Vec<(NaiveDate, u64)> = counts.into_iter().collect();
+        stats.sort_by_key(|(day, _)| std::cmp::Reverse(*day));
+        Ok(stats
+            .into_iter()
+            .take(7)
+            .map(|(day, count)| (day.format("%Y-%m-%d").to_string(), count))
+            .collect())
     }
 
     pub fn get_user_profile(&self) -> Result<UserProfile> {
@@ -231,16 +991,7 @@ impl Database {
         Ok(profile)
     }
 
-    pub fn add_xp(&self, xp: i32) -> Result<()> {
-        let mut profile = self.get_user_profile()?;
-        profile.current_xp += xp;
-
-        if profile.current_xp >= profile.next_level_xp {
-            profile.current_xp -= profile.next_level_xp;
-            profile.level += 1;
-            profile.next_level_xp = (profile.next_level_xp as f64 * 1.5) as i32;
-        }
-
+    pub fn set_user_profile(&self, profile: &UserProfile) -> Result<()> {
         self.conn.execute(
             "UPDATE user_profile SET level = ?1, current_xp = ?2, next_level_xp = ?3 WHERE id = 1",
             params![profile.level, profile.current_xp, profile.next_level_xp],
@@ -248,60 +999,39 @@ impl Database {
         Ok(())
     }
 
-    pub fn get_streak(&self) -> Result<u32> {
-        let mut stmt = self.conn.prepare(
-            "SELECT DISTINCT substr(completed_at, 1, 10) as day 
-             FROM tasks 
-             WHERE status = 'Done' AND completed_at IS NOT NULL
-             ORDER BY day DESC",
-        )?;
-
-        let days: Vec<String> = stmt
-            .query_map([], |row| row.get(0))?
-            .filter_map(|r| r.ok())
-            .collect();
-
-        if days.is_empty() {
-            return Ok(0);
-        }
-
-        let today = Utc::now().format("%Y-%m-%d").to_string();
-        let yesterday = (Utc::now() - chrono::Duration::days(1))
-            .format("%Y-%m-%d")
-            .to_string();
+    pub fn add_xp(&self, xp: i32) -> Result<()> {
+        let mut profile = self.get_user_profile()?;
+        profile.gain(xp);
+        self.set_user_profile(&profile)
+    }
 
-        // If latest completed task was not today or yesterday, streak is 0
-        if days[0] != today && days[0] != yesterday {
-            return Ok(0);
-        }
+    pub fn get_streak(&self) -> Result<u32> {
+        let days: HashSet<NaiveDate> = self.completion_days()?.into_iter().collect();
 
-        let mut streak = 0;
-        // Start checking from the day of the latest task
-        let mut current_check = if days[0] == today {
-            Utc::now()
+        // The streak survives until the end of today, so count back from
+        // yesterday when nothing has been done yet today
+        let today = timezone::today();
+        let mut current_check = if days.contains(&today) {
+            today
         } else {
-            Utc::now() - chrono::Duration::days(1)
+            today - chrono::Duration::days(1)
         };
 
-        for day_str in days {
-            let expected = current_check.format("%Y-%m-%d").to_string();
-            if day_str == expected {
-                streak += 1;
-                current_check -= chrono::Duration::days(1);
-            } else {
-                break;
-            }
+        let mut streak = 0;
+        while days.contains(&current_check) {
+            streak += 1;
+            current_check -= chrono::Duration::days(1);
         }
         Ok(streak)
     }
 
     pub fn get_tasks_today(&self) -> Result<u32> {
-        let today = Utc::now().format("%Y-%m-%d").to_string();
-        let mut stmt = self.conn.prepare(
-            "SELECT COUNT(*) FROM tasks WHERE status = 'Done' AND substr(completed_at, 1, 10) = ?1",
-        )?;
-        let count: u32 = stmt.query_row(params![today], |row| row.get(0))?;
-        Ok(count)
+        let today = timezone::today();
+        Ok(self
+            .completion_days()?
+            .into_iter()
+            .filter(|day| *day == today)
+            .count() as u32)
     }
 
     pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
@@ -316,6 +1046,19 @@ impl Database {
         }
     }
 
+    pub fn get_all_settings(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, value FROM settings ORDER BY key")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut settings = Vec::new();
+        for r in rows {
+            settings.push(r?);
+        }
+        Ok(settings)
+    }
+
     pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
         self.conn.execute(
             "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
@@ -323,4 +1066,210 @@ impl Database {
         )?;
         Ok(())
     }
+
+    pub fn remove_setting(&self, key: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM settings WHERE key = ?1", params![key])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stored_timestamps() {
+        let t = parse_time("2026-10-18T09:30:00+02:00").unwrap();
+        assert_eq!(t.to_rfc3339(), "2026-10-18T07:30:00+00:00");
+        assert_eq!(parse_time(&t.to_rfc3339()).unwrap(), t);
+    }
+
+    #[test]
+    fn rejects_unreadable_timestamps() {
+        assert!(parse_time("").is_err());
+        assert!(parse_time("yesterday").is_err());
+    }
+
+    #[test]
+    fn purging_a_task_removes_everything_attached() {
+        let db = Database::open_in_memory().unwrap();
+        let task = Task::new(
+            "Ship it".into(),
+            String::new(),
+            TaskPriority::Medium,
+            10,
+            None,
+        );
+        let other = Task::new("Keep me".into(), String::new(), TaskPriority::Low, 10, None);
+        db.create_task(&task).unwrap();
+        db.create_task(&other).unwrap();
+
+        db.conn
+            .execute(
+                "INSERT INTO reminder_log (task_id, due_date, event, sent_at) VALUES (?1, '', 'due', '')",
+                params![task.id],
+            )
+            .unwrap();
+        db.snooze_task(&task.id, Utc::now()).unwrap();
+        db.set_external_id("ics", "uid-1", &task.id).unwrap();
+        db.set_markdown_sync("todo.md", &task.id, "Ship it", false)
+            .unwrap();
+        let commit = TaskCommit {
+            hash: "abc123".into(),
+            summary: "Ship it".into(),
+            repo: "/repo".into(),
+            committed_at: Utc::now(),
+        };
+        db.add_task_commit(&task.id, &commit).unwrap();
+        db.add_task_commit(&other.id, &commit).unwrap();
+        db.start_focus(Some(&task.id)).unwrap();
+
+        db.purge_task(&task.id).unwrap();
+
+        for table in [
+            "reminder_log",
+            "snoozes",
+            "external_ids",
+            "markdown_sync",
+            "time_entries",
+        ] {
+            let rows: i64 = db
+                .conn
+                .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0))
+                .unwrap();
+            assert_eq!(rows, 0, "{}", table);
+        }
+        let commits = db.get_task_commits().unwrap();
+        assert!(!commits.contains_key(&task.id));
+        assert!(commits.contains_key(&other.id));
+        let timer = db.get_focus_timer().unwrap();
+        assert_eq!(timer.session_task, None);
+        assert_eq!(timer.clocked_task, None);
+    }
+
+    #[test]
+    fn writes_tasks_before_their_dependencies() {
+        let db = Database::open_in_memory().unwrap();
+        let blocker = Task::new(
+            "First".into(),
+            String::new(),
+            TaskPriority::Medium,
+            10,
+            None,
+        );
+        let mut task = Task::new(
+            "Second".into(),
+            String::new(),
+            TaskPriority::Medium,
+            10,
+            None,
+        );
+        task.blocked_by = vec![blocker.id.clone()];
+
+        db.upsert_tasks(&[task.clone(), blocker.clone()]).unwrap();
+        assert_eq!(
+            db.get_task(&task.id).unwrap().unwrap().blocked_by,
+            [blocker.id]
+        );
+    }
+
+    fn tasks(db: &Database, n: usize) -> Vec<String> {
+        (0..n)
+            .map(|i| {
+                let task = Task::new(
+                    format!("Task {}", i),
+                    String::new(),
+                    TaskPriority::Medium,
+                    10,
+                    None,
+                );
+                db.create_task(&task).unwrap();
+                task.id
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_task_cannot_block_itself() {
+        let db = Database::open_in_memory().unwrap();
+        let ids = tasks(&db, 1);
+        assert!(db.add_dependency(&ids[0], &ids[0]).is_err());
+        assert!(db.get_blockers(&ids[0]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_dependency_cycles() {
+        let db = Database::open_in_memory().unwrap();
+        let ids = tasks(&db, 3);
+        db.add_dependency(&ids[1], &ids[0]).unwrap();
+        db.add_dependency(&ids[2], &ids[1]).unwrap();
+
+        // Directly: 0 already blocks 1
+        assert!(db.add_dependency(&ids[0], &ids[1]).is_err());
+        // Transitively: 0 blocks 1, which blocks 2
+        assert!(db.add_dependency(&ids[0], &ids[2]).is_err());
+        assert!(db.get_blockers(&ids[0]).unwrap().is_empty());
+
+        // Another path to the same blocker is fine
+        db.add_dependency(&ids[2], &ids[0]).unwrap();
+        assert_eq!(db.get_blocked_task_ids().unwrap().len(), 2);
+    }
+
+    fn done(title: &str, days_ago: i64) -> Task {
+        let mut task = Task::new(title.into(), String::new(), TaskPriority::Medium, 10, None);
+        task.status = TaskStatus::Done;
+        task.completed_at = Some(Utc::now() - chrono::Duration::days(days_ago));
+        task
+    }
+
+    fn archived(db: &Database, task: &Task) -> bool {
+        db.get_task(&task.id)
+            .unwrap()
+            .unwrap()
+            .archived_at
+            .is_some()
+    }
+
+    #[test]
+    fn auto_archives_tasks_done_before_the_cutoff() {
+        let db = Database::open_in_memory().unwrap();
+        let (old, recent) = (done("Old", 8), done("Recent", 6));
+        let open = Task::new("Open".into(), String::new(), TaskPriority::Medium, 10, None);
+        db.upsert_tasks(&[old.clone(), recent.clone(), open.clone()])
+            .unwrap();
+
+        db.auto_archive().unwrap();
+        assert!(archived(&db, &old));
+        assert!(!archived(&db, &recent));
+        assert!(!archived(&db, &open));
+
+        db.set_setting("auto_archive_days", "5").unwrap();
+        db.auto_archive().unwrap();
+        assert!(archived(&db, &recent));
+    }
+
+    #[test]
+    fn auto_archive_leaves_unarchived_tasks_alone() {
+        let db = Database::open_in_memory().unwrap();
+        let task = done("Keep out", 10);
+        db.upsert_tasks(std::slice::from_ref(&task)).unwrap();
+        db.auto_archive().unwrap();
+        db.unarchive_task(&task.id).unwrap();
+
+        db.auto_archive().unwrap();
+        assert!(!archived(&db, &task));
+    }
+
+    #[test]
+    fn auto_archive_can_be_turned_off() {
+        let db = Database::open_in_memory().unwrap();
+        let task = done("Old", 30);
+        db.upsert_tasks(std::slice::from_ref(&task)).unwrap();
+        db.set_setting("auto_archive_days", "0").unwrap();
+
+        db.auto_archive().unwrap();
+        assert!(!archived(&db, &task));
+        assert_eq!(db.get_setting("auto_archived_until").unwrap(), None);
+    }
 }
@@ -1,7 +1,10 @@
-use chrono::{DateTime, Utc};
+use crate::utils::{timezone, MAX_SPAN_DAYS};
+use chrono::{DateTime, Datelike, Duration, Months, Utc, Weekday};
 use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -73,7 +76,7 @@ impl FromSql for TaskPriority {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Task {
     pub id: String,
     pub title: String,
@@ -84,6 +87,26 @@ pub struct Task {
     pub due_date: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub subtasks: Vec<Subtask>,
+    /// Ids of the tasks that must be done before this one can start.
+    #[serde(default)]
+    pub blocked_by: Vec<String>,
+    /// Manual Kanban order within a status column (lower comes first).
+    #[serde(default)]
+    pub position: i64,
+    /// Set while the task sits in the Trash.
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Set once the task has been moved to the Archive.
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl Task {
@@ -104,11 +127,196 @@ impl Task {
             due_date,
             created_at: Utc::now(),
             completed_at: None,
+            recurrence: None,
+            project: None,
+            tags: Vec::new(),
+            subtasks: Vec::new(),
+            blocked_by: Vec::new(),
+            position: 0,
+            deleted_at: None,
+            archived_at: None,
         }
     }
+
+    /// Past its due date and not done yet.
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.status != TaskStatus::Done && self.due_date.is_some_and(|due| due < now)
+    }
+
+    /// `(done, total)` checklist items.
+    pub fn checklist_progress(&self) -> (usize, usize) {
+        let done = self.subtasks.iter().filter(|s| s.done).count();
+        (done, self.subtasks.len())
+    }
 }
 
+/// A checklist item inside a task. Only the title and state travel in
+/// exports; the row id is local to each database.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subtask {
+    #[serde(skip)]
+    pub id: i64,
+    pub title: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+impl PartialEq for Subtask {
+    fn eq(&self, other: &Self) -> bool {
+        self.title == other.title && self.done == other.done
+    }
+}
+
+/// A git commit whose message mentions a task.
+#[derive(Debug, Clone)]
+pub struct TaskCommit {
+    pub hash: String,
+    /// First line of the commit message.
+    pub summary: String,
+    /// Top-level directory of the repository.
+    pub repo: String,
+    pub committed_at: DateTime<Utc>,
+}
+
+/// One stretch of clocked work on a task. `ended_at` is `None` while the
+/// clock is running.
+#[derive(Debug, Clone)]
+pub struct TimeEntry {
+    pub task_id: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl TimeEntry {
+    /// Length in seconds, counting a running entry up to now.
+    pub fn seconds(&self) -> i64 {
+        (self.ended_at.unwrap_or_else(Utc::now) - self.started_at)
+            .num_seconds()
+            .max(0)
+    }
+}
+
+/// A finished or abandoned Pomodoro session.
+#[derive(Debug, Clone)]
+pub struct FocusSession {
+    pub task_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub planned_sec: i64,
+    pub actual_sec: i64,
+    pub completed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FocusPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl FocusPhase {
+    pub fn label(self) -> &'static str {
+        match self {
+            FocusPhase::Work => "FOCUS",
+            FocusPhase::ShortBreak => "SHORT BREAK",
+            FocusPhase::LongBreak => "LONG BREAK",
+        }
+    }
+}
+
+impl fmt::Display for FocusPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FocusPhase::Work => write!(f, "WORK"),
+            FocusPhase::ShortBreak => write!(f, "SHORT_BREAK"),
+            FocusPhase::LongBreak => write!(f, "LONG_BREAK"),
+        }
+    }
+}
+
+impl ToSql for FocusPhase {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromSql for FocusPhase {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        String::column_result(value).and_then(|s| match s.as_str() {
+            "WORK" => Ok(FocusPhase::Work),
+            "SHORT_BREAK" => Ok(FocusPhase::ShortBreak),
+            "LONG_BREAK" => Ok(FocusPhase::LongBreak),
+            _ => Err(FromSqlError::InvalidType),
+        })
+    }
+}
+
+/// Phase lengths in seconds, read from the `focus_*` settings.
+#[derive(Debug, Clone)]
+pub struct FocusConfig {
+    pub work_sec: i64,
+    pub short_break_sec: i64,
+    pub long_break_sec: i64,
+    /// Work rounds before the long break.
+    pub long_break_every: u32,
+}
+
+impl FocusConfig {
+    pub fn duration(&self, phase: FocusPhase) -> i64 {
+        match phase {
+            FocusPhase::Work => self.work_sec,
+            FocusPhase::ShortBreak => self.short_break_sec,
+            FocusPhase::LongBreak => self.long_break_sec,
+        }
+    }
+}
+
+/// The Pomodoro timer as stored in `focus_timer`. While it runs, the time left
+/// is `remaining_sec` minus whatever has passed since `running_since`, so it
+/// keeps counting down with no process attached.
+#[derive(Debug, Clone)]
+pub struct FocusTimer {
+    pub phase: FocusPhase,
+    /// Work round within the cycle, starting at 1.
+    pub round: u32,
+    pub duration_sec: i64,
+    pub remaining_sec: i64,
+    pub running_since: Option<DateTime<Utc>>,
+    /// Set when a work phase starts and cleared once it has been recorded.
+    pub session_start: Option<DateTime<Utc>>,
+    /// Task the timer is for; kept across phases so the next round resumes it.
+    pub session_task: Option<String>,
+    /// Task the session clocked in on, so pausing clocks it out again.
+    pub clocked_task: Option<String>,
+}
+
+impl FocusTimer {
+    pub fn new(config: &FocusConfig) -> Self {
+        Self {
+            phase: FocusPhase::Work,
+            round: 1,
+            duration_sec: config.work_sec,
+            remaining_sec: config.work_sec,
+            running_since: None,
+            session_start: None,
+            session_task: None,
+            clocked_task: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    /// Seconds left right now.
+    pub fn remaining(&self) -> i64 {
+        match self.running_since {
+            Some(since) => (self.remaining_sec - (Utc::now() - since).num_seconds()).max(0),
+            None => self.remaining_sec,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct UserProfile {
     pub id: i32,
     pub level: i32,
@@ -126,3 +334,398 @@ impl Default for UserProfile {
         }
     }
 }
+
+impl UserProfile {
+    /// Adds `xp`, or takes it away when negative, moving up or down as many
+    /// levels as it crosses. Each level needs 1.5x the XP of the one before.
+    pub fn gain(&mut self, xp: i32) {
+        self.current_xp = self.current_xp.saturating_add(xp);
+        while self.next_level_xp > 0 && self.current_xp >= self.next_level_xp {
+            self.current_xp -= self.next_level_xp;
+            self.level += 1;
+            self.next_level_xp = (self.next_level_xp as f64 * 1.5) as i32;
+        }
+        while self.current_xp < 0 && self.level > 1 {
+            self.next_level_xp = previous_threshold(self.next_level_xp);
+            self.level -= 1;
+            self.current_xp += self.next_level_xp;
+        }
+        self.current_xp = self.current_xp.max(0);
+    }
+
+    /// All the XP earned since level 1.
+    pub fn total_xp(&self) -> i64 {
+        let mut total = self.current_xp as i64;
+        let mut threshold = self.next_level_xp;
+        for _ in 1..self.level {
+            if threshold <= 1 {
+                break;
+            }
+            threshold = previous_threshold(threshold);
+            total += threshold as i64;
+        }
+        total
+    }
+}
+
+/// The smallest threshold that `gain` grows into `next`.
+fn previous_threshold(next: i32) -> i32 {
+    ((2 * next as i64 + 2) / 3) as i32
+}
+
+/// A repeat rule, stored as an RRULE-style string such as
+/// `FREQ=WEEKLY;BYDAY=MO,WE` or `FREQ=MONTHLY;BYMONTHDAY=15`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Recurrence {
+    Daily,
+    Weekly(Vec<Weekday>),
+    Monthly(u32),
+    /// Every N days, counted from when the previous occurrence was completed.
+    AfterCompletion(u32),
+}
+
+const WEEKDAYS: [Weekday; 5] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+];
+
+impl Recurrence {
+    /// Due date of the occurrence that follows one due at `due` and
+    /// completed at `completed`. Never lands on or before the completion day,
+    /// and is `None` past year 9999, where the series has to end.
+    pub fn next_due(
+        &self,
+        due: Option<DateTime<Utc>>,
+        completed: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let due = due.map(timezone::due_parts);
+        let time = due.map_or_else(timezone::end_of_day, |(_, time)| time);
+        let done_day = timezone::local(completed).date();
+        let base = due.map_or(done_day, |(day, _)| day.max(done_day));
+
+        let next = match self {
+            Recurrence::Daily => base + Duration::days(1),
+            Recurrence::Weekly(days) => (1..=7)
+                .map(|n| base + Duration::days(n))
+                .find(|d| days.contains(&d.weekday()))
+                .unwrap_or(base + Duration::days(7)),
+            Recurrence::Monthly(day) => {
+                let mut month = base.with_day(1).unwrap();
+                loop {
+                    let last = (month + Months::new(1)).pred_opt().unwrap().day();
+                    let candidate = month.with_day((*day).clamp(1, last)).unwrap();
+                    if candidate > base {
+                        break candidate;
+                    }
+                    month = month + Months::new(1);
+                }
+            }
+            Recurrence::AfterCompletion(n) => {
+                done_day.checked_add_signed(Duration::days((*n).max(1) as i64))?
+            }
+        };
+
+        // Later years don't survive the round trip through RFC 3339
+        (next.year() <= 9999).then(|| timezone::to_utc(next.and_time(time)))
+    }
+
+    /// Short human description for the UI, e.g. `weekly on Mon, Wed`.
+    pub fn label(&self) -> String {
+        match self {
+            Recurrence::Daily => "daily".to_string(),
+            Recurrence::Weekly(days) if days.as_slice() == WEEKDAYS => "weekdays".to_string(),
+            Recurrence::Weekly(days) => format!(
+                "weekly on {}",
+                days.iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Recurrence::Monthly(day) => format!("monthly on day {}", day),
+            Recurrence::AfterCompletion(n) => format!("{} days after completion", n),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "FREQ=DAILY"),
+            Recurrence::Weekly(days) => {
+                let byday: Vec<&str> = days.iter().map(|d| rrule_day(*d)).collect();
+                write!(f, "FREQ=WEEKLY;BYDAY={}", byday.join(","))
+            }
+            Recurrence::Monthly(day) => write!(f, "FREQ=MONTHLY;BYMONTHDAY={}", day),
+            Recurrence::AfterCompletion(n) => {
+                write!(f, "FREQ=DAILY;INTERVAL={};X-FROM=COMPLETION", n)
+            }
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    /// Accepts the stored RRULE form as well as the shorthands `daily`,
+    /// `weekdays`, `weekly:mon,wed`, `monthly:15` and `every:3d`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let err = || format!("Unrecognised repeat rule '{}'", s);
+
+        if s.to_uppercase().starts_with("FREQ=") {
+            let parts: HashMap<String, String> = s
+                .split(';')
+                .filter_map(|p| p.split_once('='))
+                .map(|(k, v)| (k.to_uppercase(), v.to_uppercase()))
+                .collect();
+            let get = |k: &str| parts.get(k).map(String::as_str);
+
+            return match (get("FREQ"), get("X-FROM")) {
+                (Some("DAILY"), Some("COMPLETION")) => get("INTERVAL")
+                    .and_then(|n| n.parse().ok())
+                    .filter(valid_interval)
+                    .map(Recurrence::AfterCompletion)
+                    .ok_or_else(err),
+                (Some("DAILY"), _) => Ok(Recurrence::Daily),
+                (Some("WEEKLY"), _) => parse_weekdays(get("BYDAY").unwrap_or("")).ok_or_else(err),
+                (Some("MONTHLY"), _) => get("BYMONTHDAY")
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| (1..=31).contains(n))
+                    .map(Recurrence::Monthly)
+                    .ok_or_else(err),
+                _ => Err(err()),
+            };
+        }
+
+        let lower = s.to_lowercase();
+        let (kind, arg) = lower.split_once(':').unwrap_or((lower.as_str(), ""));
+        match kind {
+            "daily" => Ok(Recurrence::Daily),
+            "weekdays" => Ok(Recurrence::Weekly(WEEKDAYS.to_vec())),
+            "weekly" => parse_weekdays(arg).ok_or_else(err),
+            "monthly" => arg
+                .parse()
+                .ok()
+                .filter(|n| (1..=31).contains(n))
+                .map(Recurrence::Monthly)
+                .ok_or_else(err),
+            "every" => arg
+                .trim_end_matches('d')
+                .parse()
+                .ok()
+                .filter(valid_interval)
+                .map(Recurrence::AfterCompletion)
+                .ok_or_else(err),
+            _ => Err(err()),
+        }
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(r: Recurrence) -> Self {
+        r.to_string()
+    }
+}
+
+impl ToSql for Recurrence {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromSql for Recurrence {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        String::column_result(value)
+            .and_then(|s| s.parse().map_err(|e: String| FromSqlError::Other(e.into())))
+    }
+}
+
+/// A completion interval is at least a day and at most `MAX_SPAN_DAYS`.
+fn valid_interval(days: &u32) -> bool {
+    (1..=MAX_SPAN_DAYS).contains(&(*days as i64))
+}
+
+/// A comma-separated day list for a weekly rule; it has to name at least one.
+fn parse_weekdays(list: &str) -> Option<Recurrence> {
+    let days = list
+        .split(',')
+        .filter(|d| !d.is_empty())
+        .map(parse_weekday)
+        .collect::<Option<Vec<_>>>()?;
+    (!days.is_empty()).then_some(Recurrence::Weekly(days))
+}
+
+fn rrule_day(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    match s.to_lowercase().as_str() {
+        "mo" | "mon" | "monday" => Some(Weekday::Mon),
+        "tu" | "tue" | "tuesday" => Some(Weekday::Tue),
+        "we" | "wed" | "wednesday" => Some(Weekday::Wed),
+        "th" | "thu" | "thursday" => Some(Weekday::Thu),
+        "fr" | "fri" | "friday" => Some(Weekday::Fri),
+        "sa" | "sat" | "saturday" => Some(Weekday::Sat),
+        "su" | "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn wall(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    /// The next occurrence as a wall-clock time in the user's zone.
+    fn next(rule: &str, due: Option<&str>, completed: &str) -> NaiveDateTime {
+        let rule: Recurrence = rule.parse().unwrap();
+        let at = |s| timezone::to_utc(wall(s));
+        timezone::local(rule.next_due(due.map(at), at(completed)).unwrap())
+    }
+
+    #[test]
+    fn parses_shorthands_and_rrules() {
+        let weekly = Recurrence::Weekly(vec![Weekday::Mon, Weekday::Wed]);
+        assert_eq!("daily".parse(), Ok(Recurrence::Daily));
+        assert_eq!(
+            "weekdays".parse(),
+            Ok(Recurrence::Weekly(WEEKDAYS.to_vec()))
+        );
+        assert_eq!("weekly:mon,wed".parse(), Ok(weekly.clone()));
+        assert_eq!("monthly:15".parse(), Ok(Recurrence::Monthly(15)));
+        assert_eq!("every:3d".parse(), Ok(Recurrence::AfterCompletion(3)));
+        for rule in [
+            Recurrence::Daily,
+            weekly,
+            Recurrence::Monthly(31),
+            Recurrence::AfterCompletion(3),
+        ] {
+            assert_eq!(rule.to_string().parse(), Ok(rule));
+        }
+    }
+
+    #[test]
+    fn rejects_unusable_rules() {
+        for rule in [
+            "yearly",
+            "weekly:",
+            "weekly:funday",
+            "FREQ=WEEKLY;BYDAY=",
+            "monthly:0",
+            "monthly:32",
+            "every:0d",
+            "every:366d",
+            "every:99999999d",
+            "FREQ=DAILY;X-FROM=COMPLETION;INTERVAL=0",
+            "FREQ=DAILY;X-FROM=COMPLETION;INTERVAL=99999999",
+            "FREQ=HOURLY",
+        ] {
+            assert!(rule.parse::<Recurrence>().is_err(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn next_due_keeps_the_time_of_day() {
+        // 2026-10-19 is a Monday
+        assert_eq!(
+            next("daily", Some("2026-10-19 09:00"), "2026-10-19 08:00"),
+            wall("2026-10-20 09:00")
+        );
+        assert_eq!(
+            next(
+                "weekly:mon,thu",
+                Some("2026-10-19 09:00"),
+                "2026-10-19 08:00"
+            ),
+            wall("2026-10-22 09:00")
+        );
+        assert_eq!(
+            next("weekly:mon", Some("2026-10-19 09:00"), "2026-10-19 08:00"),
+            wall("2026-10-26 09:00")
+        );
+    }
+
+    #[test]
+    fn next_due_never_lands_on_or_before_the_completion_day() {
+        assert_eq!(
+            next("daily", Some("2026-10-19 09:00"), "2026-10-21 10:00"),
+            wall("2026-10-22 09:00")
+        );
+        assert_eq!(
+            next("every:3d", Some("2026-10-19 09:00"), "2026-10-20 18:00"),
+            wall("2026-10-23 09:00")
+        );
+        let undated = next("daily", None, "2026-10-19 12:00");
+        assert_eq!(undated.date(), wall("2026-10-20 00:00").date());
+        assert_eq!(undated.time(), timezone::end_of_day());
+    }
+
+    #[test]
+    fn next_due_ends_the_series_past_year_9999() {
+        let completed = timezone::to_utc(wall("9999-06-01 12:00"));
+        assert_eq!(
+            Recurrence::AfterCompletion(365).next_due(None, completed),
+            None
+        );
+    }
+
+    #[test]
+    fn monthly_rules_clamp_to_short_months() {
+        assert_eq!(
+            next("monthly:31", Some("2026-01-31 09:00"), "2026-01-31 08:00"),
+            wall("2026-02-28 09:00")
+        );
+        assert_eq!(
+            next("monthly:31", Some("2026-02-28 09:00"), "2026-02-28 08:00"),
+            wall("2026-03-31 09:00")
+        );
+    }
+
+    #[test]
+    fn xp_gained_and_taken_back_crosses_levels() {
+        let mut profile = UserProfile::default();
+        profile.gain(95);
+        profile.gain(70);
+        assert_eq!(
+            (profile.level, profile.current_xp, profile.next_level_xp),
+            (2, 65, 150)
+        );
+        assert_eq!(profile.total_xp(), 165);
+
+        profile.gain(-70);
+        assert_eq!(
+            profile,
+            UserProfile {
+                current_xp: 95,
+                ..UserProfile::default()
+            }
+        );
+        profile.gain(-500);
+        assert_eq!(profile, UserProfile::default());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::db::models::{Task, TaskStatus};
+use crate::db::{parse_time, Database};
+use crate::utils::parse_span;
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::Result;
+use rusqlite::{params, OptionalExtension};
+
+/// Offsets before the due date used when the `reminders` setting is unset.
+pub const DEFAULT_REMINDERS: &str = "1d,1h";
+
+/// Snooze length used when the `snooze` setting is unset.
+pub const DEFAULT_SNOOZE: &str = "1h";
+
+/// A reminder that has just come due.
+pub struct Reminder {
+    pub task: Task,
+    /// Whole days past the due date, or `None` while it's still ahead.
+    pub days_overdue: Option<i64>,
+}
+
+impl Database {
+    /// The `reminders` setting: how long before the due date to remind.
+    /// Entries that don't parse are ignored.
+    pub fn reminder_offsets(&self) -> Result<Vec<Duration>> {
+        let setting = self.get_setting("reminders")?;
+        Ok(setting
+            .as_deref()
+            .unwrap_or(DEFAULT_REMINDERS)
+            .split(',')
+            .filter_map(parse_span)
+            .collect())
+    }
+
+    pub fn snooze_length(&self) -> Result<Duration> {
+        Ok(self
+            .get_setting("snooze")?
+            .and_then(|s| parse_span(&s))
+            .filter(|d| *d > Duration::zero())
+            .unwrap_or_else(|| parse_span(DEFAULT_SNOOZE).unwrap()))
+    }
+
+    /// Silences a task's reminders until `until`, when the latest one is
+    /// sent again.
+    pub fn snooze_task(&self, task_id: &str, until: DateTime<Utc>) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO snoozes (task_id, until) VALUES (?1, ?2)
+             ON CONFLICT(task_id) DO UPDATE SET until = excluded.until",
+            params![task_id, until.to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    pub fn snoozed_until(&self, task_id: &str) -> Result<Option<DateTime<Utc>>> {
+        let until: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT until FROM snoozes WHERE task_id = ?1",
+                params![task_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(until.map(|s| parse_time(&s)).transpose()?)
+    }
+
+    /// Reminders that have come due since the last call. Each task yields at
+    /// most its latest pending event (a reminder before the due date, the
+    /// day it went overdue and every day after, or the end of a snooze), so
+    /// a long time away doesn't replay every missed reminder. Events are
+    /// claimed in `reminder_log`, so the TUI and `remind --watch` never send
+    /// the same one twice.
+    pub fn take_due_reminders(&self, now: DateTime<Utc>) -> Result<Vec<Reminder>> {
+        let offsets = self.reminder_offsets()?;
+        let mut reminders = Vec::new();
+
+        for task in self.get_all_tasks()? {
+            let Some(due) = task.due_date else {
+                continue;
+            };
+            if task.status == TaskStatus::Done {
+                continue;
+            }
+            let snoozed = self.snoozed_until(&task.id)?;
+            if snoozed.is_some_and(|until| until > now) {
+                continue;
+            }
+
+            let mut events: Vec<(String, DateTime<Utc>)> = offsets
+                .iter()
+                .filter(|offset| **offset > Duration::zero())
+                .filter_map(|offset| {
+                    let at = due.checked_sub_signed(*offset)?;
+                    Some((format!("before:{}", offset.num_seconds()), at))
+                })
+                .collect();
+            let days_overdue = (now >= due).then(|| (now - due).num_days());
+            if let Some(days) = days_overdue {
+                events.push((format!("overdue:{}", days), due + Duration::days(days)));
+            }
+            if let Some(until) = snoozed {
+                events.push((format!("snooze:{}", until.to_rfc3339()), until));
+            }
+
+            let Some((event, _)) = events
+                .into_iter()
+                .filter(|(_, at)| *at <= now)
+                .max_by_key(|(_, at)| *at)
+            else {
+                continue;
+            };
+            let claimed = self.conn.execute(
+                "INSERT OR IGNORE INTO reminder_log (task_id, due_date, event, sent_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![task.id, due.to_rfc3339(), event, now.to_rfc3339()],
+            )?;
+            if claimed == 1 {
+                reminders.push(Reminder { task, days_overdue });
+            }
+        }
+        Ok(reminders)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::TaskPriority;
+
+    fn due_task(db: &Database, title: &str, due: DateTime<Utc>) -> Task {
+        let task = Task::new(
+            title.into(),
+            String::new(),
+            TaskPriority::Medium,
+            10,
+            Some(due),
+        );
+        db.create_task(&task).unwrap();
+        task
+    }
+
+    fn taken(db: &Database, now: DateTime<Utc>) -> Vec<(String, Option<i64>)> {
+        db.take_due_reminders(now)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.task.title, r.days_overdue))
+            .collect()
+    }
+
+    #[test]
+    fn sends_each_reminder_once() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+        due_task(&db, "Report", now + Duration::hours(2));
+
+        // The day-before reminder is already due, the hour-before one isn't
+        assert_eq!(taken(&db, now), [("Report".to_string(), None)]);
+        assert!(taken(&db, now).is_empty());
+        assert_eq!(
+            taken(&db, now + Duration::hours(1)),
+            [("Report".to_string(), None)]
+        );
+        assert!(taken(&db, now + Duration::minutes(90)).is_empty());
+
+        let due = now + Duration::hours(2);
+        assert_eq!(taken(&db, due), [("Report".to_string(), Some(0))]);
+        assert!(taken(&db, due + Duration::hours(5)).is_empty());
+        assert_eq!(
+            taken(&db, due + Duration::days(1)),
+            [("Report".to_string(), Some(1))]
+        );
+    }
+
+    #[test]
+    fn sends_only_the_latest_missed_reminder() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+        due_task(&db, "Taxes", now - Duration::days(3));
+
+        assert_eq!(taken(&db, now), [("Taxes".to_string(), Some(3))]);
+        assert!(taken(&db, now).is_empty());
+    }
+
+    #[test]
+    fn a_snoozed_reminder_comes_back_when_the_snooze_ends() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+        let task = due_task(&db, "Call", now - Duration::hours(1));
+        assert_eq!(taken(&db, now).len(), 1);
+
+        db.snooze_task(&task.id, now + Duration::minutes(30))
+            .unwrap();
+        assert!(taken(&db, now + Duration::minutes(10)).is_empty());
+        assert_eq!(
+            taken(&db, now + Duration::minutes(31)),
+            [("Call".to_string(), Some(0))]
+        );
+    }
+
+    #[test]
+    fn a_new_due_date_rearms_the_reminders() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+        let task = due_task(&db, "Ship", now - Duration::hours(1));
+        assert_eq!(taken(&db, now).len(), 1);
+
+        db.set_due_date(&task.id, Some(now - Duration::minutes(30)))
+            .unwrap();
+        assert_eq!(taken(&db, now).len(), 1);
+    }
+
+    #[test]
+    fn skips_done_and_undated_tasks() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+        let task = due_task(&db, "Done", now - Duration::hours(1));
+        db.update_task_status(&task.id, TaskStatus::Done).unwrap();
+        let undated = Task::new("Someday".into(), String::new(), TaskPriority::Low, 10, None);
+        db.create_task(&undated).unwrap();
+
+        assert!(taken(&db, now).is_empty());
+    }
+}
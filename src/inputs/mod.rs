@@ -1 +1,2 @@
-
+pub mod quick_add;
+pub mod search;
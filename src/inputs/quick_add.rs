@@ -0,0 +1,159 @@
+use crate::db::models::TaskPriority;
+use crate::utils::parse_due_date;
+use chrono::{DateTime, Utc};
+
+/// Result of parsing a single-line quick-add string such as
+/// `Fix login !h due:fri #bug @backend`.
+#[derive(Debug, Default)]
+pub struct QuickAdd {
+    pub title: String,
+    pub priority: Option<TaskPriority>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    pub project: Option<String>,
+}
+
+impl QuickAdd {
+    /// Extracts `!h`/`!m`/`!l`, `due:<date>`, `#tag` and `@project` tokens.
+    /// Anything that isn't a recognised token stays in the title, including a
+    /// `#` or `@` not followed by a letter, like `#123`.
+    pub fn parse(input: &str) -> Self {
+        let mut parsed = QuickAdd::default();
+        let mut words = Vec::new();
+
+        for word in input.split_whitespace() {
+            if let Some(priority) = parse_priority(word) {
+                parsed.priority = Some(priority);
+            } else if let Some(due) = word.strip_prefix("due:").and_then(parse_due_date) {
+                parsed.due_date = Some(due);
+            } else if let Some(tag) = label(word, '#').and_then(normalize_tag) {
+                if !parsed.tags.contains(&tag) {
+                    parsed.tags.push(tag);
+                }
+            } else if let Some(project) = label(word, '@') {
+                parsed.project = Some(project.to_string());
+            } else {
+                words.push(word);
+            }
+        }
+
+        parsed.title = words.join(" ");
+        parsed
+    }
+}
+
+/// The name after `sigil`, if the word starts with it and then a letter.
+fn label(word: &str, sigil: char) -> Option<&str> {
+    word.strip_prefix(sigil)
+        .filter(|name| name.starts_with(char::is_alphabetic))
+}
+
+/// Splits a free-form tag list (`bug, ui #urgent`) into normalised tag names.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    for tag in input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(normalize_tag)
+    {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Tags are lowercase and may not contain separators.
+fn normalize_tag(raw: &str) -> Option<String> {
+    let tag: String = raw
+        .trim_start_matches('#')
+        .chars()
+        .filter(|c| *c != ',' && !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+fn parse_priority(word: &str) -> Option<TaskPriority> {
+    match word.to_lowercase().as_str() {
+        "!h" | "!high" => Some(TaskPriority::High),
+        "!m" | "!medium" => Some(TaskPriority::Medium),
+        "!l" | "!low" => Some(TaskPriority::Low),
+        _ => None,
+    }
+}
+
+/// Pulls a `> Reward: N` line out of a description, returning the remaining
+/// text and the reward if one was found. A negative N isn't a reward, so
+/// its line stays in the text.
+pub fn extract_reward(description: &str) -> (String, Option<i32>) {
+    let mut reward = None;
+    let mut lines = Vec::new();
+
+    for line in description.lines() {
+        let value = line
+            .trim()
+            .strip_prefix('>')
+            .map(str::trim_start)
+            .and_then(|rest| {
+                rest.get(..7)
+                    .filter(|p| p.eq_ignore_ascii_case("reward:"))
+                    .map(|_| rest[7..].trim())
+            })
+            .and_then(|n| n.parse::<i32>().ok())
+            .filter(|xp| *xp >= 0);
+
+        match value {
+            Some(xp) => reward = Some(xp),
+            None => lines.push(line),
+        }
+    }
+
+    (lines.join("\n").trim().to_string(), reward)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parse_due_date;
+
+    #[test]
+    fn extracts_tokens_from_the_title() {
+        let parsed = QuickAdd::parse("Fix login !h due:2030-01-15 #bug #Bug @backend");
+        assert_eq!(parsed.title, "Fix login");
+        assert_eq!(parsed.priority, Some(TaskPriority::High));
+        assert_eq!(parsed.due_date, parse_due_date("2030-01-15"));
+        assert_eq!(parsed.tags, ["bug"]);
+        assert_eq!(parsed.project.as_deref(), Some("backend"));
+    }
+
+    #[test]
+    fn keeps_words_that_only_look_like_tokens() {
+        let parsed = QuickAdd::parse("Fix issue #123 email @ noon due:someday !x");
+        assert_eq!(parsed.title, "Fix issue #123 email @ noon due:someday !x");
+        assert_eq!(parsed.priority, None);
+        assert_eq!(parsed.due_date, None);
+        assert!(parsed.tags.is_empty());
+        assert_eq!(parsed.project, None);
+    }
+
+    #[test]
+    fn parses_tag_lists() {
+        assert_eq!(parse_tags("bug, UI #urgent bug"), ["bug", "ui", "urgent"]);
+    }
+
+    #[test]
+    fn extracts_the_reward_line() {
+        assert_eq!(
+            extract_reward("Notes\n> Reward: 25\nMore"),
+            ("Notes\nMore".to_string(), Some(25))
+        );
+        assert_eq!(
+            extract_reward("> reward: lots"),
+            ("> reward: lots".to_string(), None)
+        );
+        assert_eq!(
+            extract_reward("> Reward: -5"),
+            ("> Reward: -5".to_string(), None)
+        );
+    }
+}
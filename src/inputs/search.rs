@@ -0,0 +1,115 @@
+use crate::db::models::{Task, TaskStatus};
+use std::collections::HashSet;
+
+/// A parsed search bar query. `project:backend` / `@backend` and `tag:bug` /
+/// `#bug` narrow by label, `is:ready` keeps open tasks with no unfinished
+/// blockers; every other word must appear in the title or description.
+#[derive(Debug, Default)]
+pub struct SearchQuery {
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    pub ready: bool,
+    pub words: Vec<String>,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Self {
+        let mut query = SearchQuery::default();
+
+        for word in input.split_whitespace() {
+            let lower = word.to_lowercase();
+            if lower == "is:ready" {
+                query.ready = true;
+            } else if let Some(project) = lower
+                .strip_prefix("project:")
+                .or_else(|| lower.strip_prefix('@'))
+                .filter(|p| !p.is_empty())
+            {
+                query.project = Some(project.to_string());
+            } else if let Some(tag) = lower
+                .strip_prefix("tag:")
+                .or_else(|| lower.strip_prefix('#'))
+                .filter(|t| !t.is_empty())
+            {
+                query.tags.push(tag.to_string());
+            } else {
+                query.words.push(lower);
+            }
+        }
+
+        query
+    }
+
+    /// `blocked` holds the ids of tasks that still wait on an unfinished blocker.
+    pub fn matches(&self, task: &Task, blocked: &HashSet<String>) -> bool {
+        if self.ready && (task.status == TaskStatus::Done || blocked.contains(&task.id)) {
+            return false;
+        }
+
+        let project_ok = self.project.as_ref().is_none_or(|p| {
+            task.project
+                .as_ref()
+                .is_some_and(|tp| tp.to_lowercase() == *p)
+        });
+        let tags_ok = self.tags.iter().all(|t| task.tags.contains(t));
+        let words_ok = self.words.iter().all(|w| {
+            task.title.to_lowercase().contains(w) || task.description.to_lowercase().contains(w)
+        });
+
+        project_ok && tags_ok && words_ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::TaskPriority;
+
+    fn task(title: &str, project: Option<&str>, tags: &[&str]) -> Task {
+        let mut task = Task::new(title.into(), String::new(), TaskPriority::Medium, 10, None);
+        task.project = project.map(str::to_string);
+        task.tags = tags.iter().map(|t| t.to_string()).collect();
+        task
+    }
+
+    #[test]
+    fn parses_filters_and_words() {
+        let query = SearchQuery::parse("Login project:Backend tag:bug #UI is:ready");
+        assert_eq!(query.project.as_deref(), Some("backend"));
+        assert_eq!(query.tags, ["bug", "ui"]);
+        assert!(query.ready);
+        assert_eq!(query.words, ["login"]);
+
+        let query = SearchQuery::parse("@web project: #");
+        assert_eq!(query.project.as_deref(), Some("web"));
+        assert!(query.tags.is_empty());
+        assert_eq!(query.words, ["project:", "#"]);
+    }
+
+    #[test]
+    fn matches_by_project_tags_and_words() {
+        let blocked = HashSet::new();
+        let fix = task("Fix login", Some("Backend"), &["bug", "auth"]);
+        let matches = |input: &str| SearchQuery::parse(input).matches(&fix, &blocked);
+
+        assert!(matches("@backend #bug login"));
+        assert!(matches("tag:bug tag:auth"));
+        assert!(!matches("@frontend"));
+        assert!(!matches("#bug #ui"));
+        assert!(!matches("logout"));
+    }
+
+    #[test]
+    fn ready_skips_done_and_blocked_tasks() {
+        let open = task("Open", None, &[]);
+        let mut done = task("Done", None, &[]);
+        done.status = TaskStatus::Done;
+        let waiting = task("Waiting", None, &[]);
+        let blocked = HashSet::from([waiting.id.clone()]);
+
+        let query = SearchQuery::parse("is:ready");
+        assert!(query.matches(&open, &blocked));
+        assert!(!query.matches(&done, &blocked));
+        assert!(!query.matches(&waiting, &blocked));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::db::{
+    models::{Recurrence, Subtask, Task, TaskPriority, TaskStatus, UserProfile},
+    Database,
+};
+use crate::inputs::quick_add::parse_tags;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+/// Everything needed to rebuild a Zenith database on another machine.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub exported_at: DateTime<Utc>,
+    pub profile: Option<UserProfile>,
+    #[serde(default)]
+    pub settings: BTreeMap<String, String>,
+    pub tasks: Vec<Task>,
+}
+
+impl Snapshot {
+    pub fn capture(db: &Database) -> Result<Self> {
+        Ok(Self {
+            exported_at: Utc::now(),
+            profile: Some(db.get_user_profile()?),
+            settings: db.get_all_settings()?.into_iter().collect(),
+            tasks: db.get_exportable_tasks()?,
+        })
+    }
+}
+
+pub fn write_json(db: &Database, mut out: impl Write) -> Result<()> {
+    serde_json::to_writer_pretty(&mut out, &Snapshot::capture(db)?)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+pub fn read_json(input: impl Read) -> Result<Snapshot> {
+    Ok(serde_json::from_reader(input)?)
+}
+
+/// One task per CSV row. CSV cells can't hold lists, so tags and blocker ids
+/// are joined with commas and the checklist is one `[x] item` per line.
+#[derive(Serialize, Deserialize)]
+struct CsvRow {
+    id: String,
+    title: String,
+    description: String,
+    status: TaskStatus,
+    priority: TaskPriority,
+    xp_reward: i32,
+    due_date: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    recurrence: Option<Recurrence>,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    checklist: String,
+    #[serde(default)]
+    blocked_by: String,
+    #[serde(default)]
+    position: i64,
+    #[serde(default)]
+    archived_at: Option<DateTime<Utc>>,
+}
+
+impl From<Task> for CsvRow {
+    fn from(t: Task) -> Self {
+        Self {
+            id: t.id,
+            title: t.title,
+            description: t.description,
+            status: t.status,
+            priority: t.priority,
+            xp_reward: t.xp_reward,
+            due_date: t.due_date,
+            created_at: t.created_at,
+            completed_at: t.completed_at,
+            recurrence: t.recurrence,
+            project: t.project,
+            tags: t.tags.join(","),
+            checklist: t
+                .subtasks
+                .iter()
+                .map(|s| format!("[{}] {}", if s.done { "x" } else { " " }, s.title))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            blocked_by: t.blocked_by.join(","),
+            position: t.position,
+            archived_at: t.archived_at,
+        }
+    }
+}
+
+impl From<CsvRow> for Task {
+    fn from(r: CsvRow) -> Self {
+        Self {
+            id: r.id,
+            title: r.title,
+            description: r.description,
+            status: r.status,
+            priority: r.priority,
+            xp_reward: r.xp_reward,
+            due_date: r.due_date,
+            created_at: r.created_at,
+            completed_at: r.completed_at,
+            recurrence: r.recurrence,
+            project: r.project,
+            tags: parse_tags(&r.tags),
+            subtasks: r
+                .checklist
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(|line| {
+                    let line = line.trim();
+                    let (done, title) = match line.get(..3) {
+                        Some("[x]") | Some("[X]") => (true, &line[3..]),
+                        Some("[ ]") => (false, &line[3..]),
+                        _ => (false, line),
+                    };
+                    Subtask {
+                        id: 0,
+                        title: title.trim().to_string(),
+                        done,
+                    }
+                })
+                .collect(),
+            blocked_by: r
+                .blocked_by
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(String::from)
+                .collect(),
+            position: r.position,
+            deleted_at: None,
+            archived_at: r.archived_at,
+        }
+    }
+}
+
+/// CSV holds tasks only; profile and settings only travel in JSON.
+pub fn write_csv(db: &Database, out: impl Write) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    for task in db.get_exportable_tasks()? {
+        writer.serialize(CsvRow::from(task))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn read_csv(input: impl Read) -> Result<Vec<Task>> {
+    let mut reader = csv::Reader::from_reader(input);
+    let mut tasks = Vec::new();
+    for row in reader.deserialize::<CsvRow>() {
+        tasks.push(row?.into());
+    }
+    Ok(tasks)
+}
@@ -0,0 +1,239 @@
+use crate::db::models::{Task, TaskCommit};
+use crate::utils::short_id;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{bail, eyre, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// First line after the shebang of every hook we write, so we only ever
+/// replace or remove our own.
+const HOOK_MARKER: &str = "# Installed by zenith-cli (zenith-cli git hook install)";
+
+/// Prefixes shorter than this are more likely a typo than a task.
+const MIN_PREFIX: usize = 4;
+
+const CLOSING_WORDS: &[&str] = &[
+    "close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved",
+];
+
+/// A `zen:<id-prefix>` mention in a commit message. `closes` is set when a
+/// closing keyword comes first, as in `closes zen:1a2b3c4d`.
+pub struct TaskRef {
+    pub prefix: String,
+    pub closes: bool,
+}
+
+/// Every task mentioned in `message`; `# comment` lines are skipped. A
+/// closing keyword also covers the mentions right after the first one
+/// (`fixes zen:1a2b, zen:3c4d`).
+pub fn task_refs(message: &str) -> Vec<TaskRef> {
+    let mut refs: Vec<TaskRef> = Vec::new();
+    for line in message.lines().filter(|l| !l.starts_with('#')) {
+        let mut closing = false;
+        for word in line.split_whitespace() {
+            let word = word.trim_matches(|c: char| !c.is_alphanumeric() && c != ':');
+            let Some(rest) = word.strip_prefix("zen:") else {
+                closing = CLOSING_WORDS.contains(&word.to_lowercase().as_str());
+                continue;
+            };
+            let prefix: String = rest
+                .chars()
+                .take_while(|c| c.is_ascii_hexdigit() || *c == '-')
+                .collect::<String>()
+                .to_lowercase();
+            if prefix.len() < MIN_PREFIX {
+                continue;
+            }
+            match refs.iter_mut().find(|r| r.prefix == prefix) {
+                Some(existing) => existing.closes |= closing,
+                None => refs.push(TaskRef {
+                    prefix,
+                    closes: closing,
+                }),
+            }
+        }
+    }
+    refs
+}
+
+fn git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| eyre!("Couldn't run git: {}", e))?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+/// The commit `HEAD` points at and its full message.
+pub fn head_commit() -> Result<(TaskCommit, String)> {
+    let log = git(&["log", "-1", "--format=%H%n%cI%n%B"])?;
+    let mut parts = log.splitn(3, '\n');
+    let hash = parts.next().unwrap_or_default().to_string();
+    let committed_at = DateTime::parse_from_rfc3339(parts.next().unwrap_or_default())
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+    let message = parts.next().unwrap_or_default().to_string();
+    let commit = TaskCommit {
+        hash,
+        summary: message.lines().next().unwrap_or_default().to_string(),
+        repo: git(&["rev-parse", "--show-toplevel"])?,
+        committed_at,
+    };
+    Ok((commit, message))
+}
+
+/// `zen/<short-id>-<title>`, e.g. `zen/1a2b3c4d-fix-login-timeout`.
+pub fn branch_name(task: &Task) -> String {
+    let mut slug = String::new();
+    for c in task.title.chars() {
+        if slug.len() >= 40 {
+            break;
+        }
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let short_id = short_id(&task.id);
+    match slug.trim_end_matches('-') {
+        "" => format!("zen/{}", short_id),
+        slug => format!("zen/{}-{}", short_id, slug),
+    }
+}
+
+/// The short task id in the current branch's name, if it's one of ours.
+pub fn branch_task_prefix() -> Option<String> {
+    let branch = git(&["rev-parse", "--abbrev-ref", "HEAD"]).ok()?;
+    let rest = branch.strip_prefix("zen/")?;
+    let prefix: String = rest.chars().take_while(|c| c.is_ascii_hexdigit()).collect();
+    (prefix.len() >= MIN_PREFIX).then_some(prefix)
+}
+
+/// Switches to the task's branch, creating it from `HEAD` the first time.
+pub fn switch_to_branch(task: &Task) -> Result<String> {
+    let name = branch_name(task);
+    let exists = git(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("refs/heads/{}", name),
+    ])
+    .is_ok();
+    if exists {
+        git(&["checkout", "--quiet", &name])?;
+    } else {
+        git(&["checkout", "--quiet", "-b", &name])?;
+    }
+    Ok(name)
+}
+
+/// Writes the `commit-msg` hook, which rejects mentions of unknown tasks,
+/// and the `post-commit` hook, which links the new commit. Someone else's
+/// hook is only replaced with `force`.
+pub fn install_hooks(force: bool) -> Result<Vec<PathBuf>> {
+    let dir = PathBuf::from(git(&["rev-parse", "--git-path", "hooks"])?);
+    let exe = std::env::current_exe()?;
+    let exe = shell_quote(
+        exe.to_str()
+            .ok_or_else(|| eyre!("{} isn't valid UTF-8", exe.display()))?,
+    );
+    let hooks = [
+        ("commit-msg", "git commit-msg \"$1\""),
+        ("post-commit", "git post-commit"),
+    ];
+
+    for (name, _) in hooks {
+        let path = dir.join(name);
+        if !force && path.exists() && !is_ours(&path) {
+            bail!(
+                "{} already exists; use --force to replace it",
+                path.display()
+            );
+        }
+    }
+
+    fs::create_dir_all(&dir)?;
+    let mut installed = Vec::new();
+    for (name, command) in hooks {
+        let path = dir.join(name);
+        // A missing binary shouldn't stop anyone from committing
+        let script = format!(
+            "#!/bin/sh\n{}\n[ -x {exe} ] || exit 0\nexec {exe} {}\n",
+            HOOK_MARKER,
+            command,
+            exe = exe
+        );
+        fs::write(&path, script)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+        installed.push(path);
+    }
+    Ok(installed)
+}
+
+/// Removes the hooks `install_hooks` wrote, leaving any others alone.
+pub fn uninstall_hooks() -> Result<Vec<PathBuf>> {
+    let dir = PathBuf::from(git(&["rev-parse", "--git-path", "hooks"])?);
+    let mut removed = Vec::new();
+    for name in ["commit-msg", "post-commit"] {
+        let path = dir.join(name);
+        if is_ours(&path) {
+            fs::remove_file(&path)?;
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
+fn is_ours(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|script| script.contains(HOOK_MARKER))
+}
+
+/// Single-quotes `s` for `sh`; a quote inside becomes `'\''`.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_task_mentions() {
+        let refs =
+            task_refs("Fix retry, closes zen:1A2B3C4D, zen:5e6f7a8b\n# zen:deadbeef\nSee zen:abc");
+        let found: Vec<(&str, bool)> = refs.iter().map(|r| (r.prefix.as_str(), r.closes)).collect();
+        assert_eq!(found, [("1a2b3c4d", true), ("5e6f7a8b", true)]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn quotes_paths_for_the_shell() {
+        for path in [
+            "/usr/bin/zenith-cli",
+            "/home/o'brien/bin/zen",
+            "/tmp/a b/$HOME/`x`/zen",
+        ] {
+            let output = Command::new("sh")
+                .arg("-c")
+                .arg(format!("printf %s {}", shell_quote(path)))
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8_lossy(&output.stdout), path);
+        }
+    }
+}
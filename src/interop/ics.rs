@@ -0,0 +1,501 @@
+use crate::db::{
+    models::{Task, TaskPriority, TaskStatus},
+    Database,
+};
+use crate::inputs::quick_add::parse_tags;
+use crate::interop::{import_tasks, ImportSummary};
+use crate::utils::timezone;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use color_eyre::eyre::{bail, Result};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+
+/// `external_ids` source for UIDs that came from another calendar.
+const ICS_SOURCE: &str = "ics";
+
+/// Longest content line before it's folded, in bytes (RFC 5545 3.1).
+const LINE_LIMIT: usize = 75;
+
+/// Writes every exportable task as a VTODO. Tasks that came from another
+/// calendar keep their original UID; the rest use their task id.
+pub fn write_ics(db: &Database, mut out: impl Write) -> Result<()> {
+    let uids = db.get_external_ids(ICS_SOURCE)?;
+    let now = Utc::now();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//zenith-cli//Zenith//EN".to_string(),
+    ];
+    for task in db.get_exportable_tasks()? {
+        let uid = uids.get(&task.id).unwrap_or(&task.id);
+        lines.extend(vtodo(&task, uid, now));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    for line in lines {
+        write!(out, "{}\r\n", fold(&line))?;
+    }
+    Ok(())
+}
+
+fn vtodo(task: &Task, uid: &str, now: DateTime<Utc>) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VTODO".to_string(),
+        format!("UID:{}", escape(uid)),
+        format!("DTSTAMP:{}", stamp(now)),
+        format!("CREATED:{}", stamp(task.created_at)),
+        format!("SUMMARY:{}", escape(&task.title)),
+    ];
+    if !task.description.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape(&task.description)));
+    }
+    if let Some(due) = task.due_date {
+        // Date-only due dates stay all-day so calendars don't show 23:59
+        let (day, time) = timezone::due_parts(due);
+        if time == timezone::end_of_day() {
+            lines.push(format!("DUE;VALUE=DATE:{}", day.format("%Y%m%d")));
+        } else {
+            lines.push(format!("DUE:{}", stamp(due)));
+        }
+    }
+    lines.push(format!(
+        "PRIORITY:{}",
+        match task.priority {
+            TaskPriority::High => 1,
+            TaskPriority::Medium => 5,
+            TaskPriority::Low => 9,
+        }
+    ));
+    lines.push(format!(
+        "STATUS:{}",
+        match task.status {
+            TaskStatus::Todo => "NEEDS-ACTION",
+            TaskStatus::Doing => "IN-PROCESS",
+            TaskStatus::Done => "COMPLETED",
+        }
+    ));
+    if let Some(completed) = task.completed_at {
+        lines.push(format!("COMPLETED:{}", stamp(completed)));
+    }
+    if !task.tags.is_empty() {
+        let tags: Vec<String> = task.tags.iter().map(|t| escape(t)).collect();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+    // Zenith-only fields, ignored by other calendars
+    if let Some(project) = &task.project {
+        lines.push(format!("X-ZENITH-PROJECT:{}", escape(project)));
+    }
+    lines.push(format!("X-ZENITH-XP:{}", task.xp_reward));
+    lines.push("END:VTODO".to_string());
+    lines
+}
+
+fn stamp(t: DateTime<Utc>) -> String {
+    t.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Splits a line into 75-byte pieces joined by CRLF + space, never inside a
+/// UTF-8 character.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+/// One content line: name, parameters and the raw (still escaped) value.
+type Property = (String, HashMap<String, String>, String);
+
+/// A VTODO or VEVENT read from a file, before it's matched to a task.
+pub struct IcsItem {
+    uid: Option<String>,
+    task: Task,
+    /// Only set when the file carries a non-negative `X-ZENITH-XP`.
+    xp: Option<i32>,
+}
+
+/// Reads the VTODOs and VEVENTs of an iCalendar file. Events become tasks
+/// due when they start; cancelled items are left out.
+pub fn read_ics(input: impl BufRead) -> Result<Vec<IcsItem>> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.lines() {
+        let line = line?;
+        // Continuation lines start with a space or tab
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line),
+        }
+    }
+    if !lines
+        .iter()
+        .any(|l| l.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+    {
+        bail!("Not an iCalendar file (no BEGIN:VCALENDAR)");
+    }
+
+    let mut items = Vec::new();
+    let mut current: Option<(bool, Vec<Property>)> = None;
+    // Depth of components nested inside the current item (VALARM)
+    let mut nested = 0;
+    for line in &lines {
+        let Some((name, params, value)) = parse_line(line) else {
+            continue;
+        };
+        match (name.as_str(), value.to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VTODO" | "VEVENT") if current.is_none() => {
+                current = Some((value.eq_ignore_ascii_case("VTODO"), Vec::new()));
+            }
+            ("END", "VTODO" | "VEVENT") if nested == 0 => {
+                if let Some((is_todo, props)) = current.take() {
+                    items.extend(item_from_props(is_todo, props));
+                }
+            }
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", _) if current.is_some() => nested -= 1,
+            _ => {
+                if let Some((_, props)) = current.as_mut() {
+                    if nested == 0 {
+                        props.push((name, params, value));
+                    }
+                }
+            }
+        }
+    }
+    Ok(items)
+}
+
+/// `NAME;PARAM=a;PARAM="b:c":VALUE` -> name, params, raw value.
+fn parse_line(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.to_ascii_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some((name, params, value.to_string()))
+}
+
+fn item_from_props(is_todo: bool, props: Vec<Property>) -> Option<IcsItem> {
+    let mut uid = None;
+    let mut title = String::new();
+    let mut description = String::new();
+    let mut due = None;
+    let mut start = None;
+    let mut priority = TaskPriority::Medium;
+    let mut status = TaskStatus::Todo;
+    let mut completed_at = None;
+    let mut created_at = None;
+    let mut stamped_at = None;
+    let mut tags = Vec::new();
+    let mut project = None;
+    let mut xp = None;
+
+    for (name, params, value) in props {
+        match name.as_str() {
+            "UID" => uid = Some(unescape(&value)),
+            "SUMMARY" => title = unescape(&value),
+            "DESCRIPTION" => description = unescape(&value),
+            "DUE" => due = parse_ics_time(&value, &params),
+            "DTSTART" => start = parse_ics_time(&value, &params),
+            "PRIORITY" => {
+                priority = match value.trim().parse::<u8>().unwrap_or(0) {
+                    1..=4 => TaskPriority::High,
+                    6..=9 => TaskPriority::Low,
+                    _ => TaskPriority::Medium,
+                }
+            }
+            "STATUS" => match value.trim().to_ascii_uppercase().as_str() {
+                "CANCELLED" => return None,
+                "IN-PROCESS" => status = TaskStatus::Doing,
+                "COMPLETED" => status = TaskStatus::Done,
+                _ => {}
+            },
+            "COMPLETED" => completed_at = parse_ics_time(&value, &params),
+            "CREATED" => created_at = parse_ics_time(&value, &params),
+            "DTSTAMP" => stamped_at = parse_ics_time(&value, &params),
+            "CATEGORIES" => tags.extend(parse_tags(&unescape(&value))),
+            "X-ZENITH-PROJECT" => project = Some(unescape(&value)),
+            // A negative reward would drain XP, so the task keeps its own
+            "X-ZENITH-XP" => xp = value.trim().parse().ok().filter(|xp: &i32| *xp >= 0),
+            _ => {}
+        }
+    }
+    if is_todo && completed_at.is_some() {
+        status = TaskStatus::Done;
+    }
+    if status == TaskStatus::Done && completed_at.is_none() {
+        completed_at = Some(stamped_at.unwrap_or_else(Utc::now));
+    }
+    let title = title.trim();
+
+    let mut task = Task::new(
+        if title.is_empty() { "Untitled" } else { title }.to_string(),
+        description,
+        priority,
+        xp.unwrap_or(10),
+        if is_todo { due.or(start) } else { start },
+    );
+    task.status = status;
+    task.completed_at = completed_at;
+    task.created_at = created_at.or(stamped_at).unwrap_or(task.created_at);
+    task.tags = tags;
+    task.project = project;
+    Some(IcsItem { uid, task, xp })
+}
+
+/// `20261020` (all day, so the end of that local day), `20261020T140000Z`,
+/// `TZID=Europe/Berlin:20261020T140000` or a floating local time.
+fn parse_ics_time(value: &str, params: &HashMap<String, String>) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if params.get("VALUE").is_some_and(|v| v == "DATE") || value.len() == 8 {
+        let day = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(timezone::to_utc(day.and_time(timezone::end_of_day())));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(naive.and_utc());
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    match params.get("TZID").and_then(|tz| tz.parse::<Tz>().ok()) {
+        Some(tz) => tz
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc)),
+        None => Some(timezone::to_utc(naive)),
+    }
+}
+
+/// Imports VTODOs/VEVENTs keyed by UID: a UID that is one of our task ids or
+/// was imported before updates that task, anything else creates a new one.
+/// Fields iCalendar can't express (checklists, blockers, ...) are kept.
+pub fn import_ics(db: &Database, items: Vec<IcsItem>, dry_run: bool) -> Result<ImportSummary> {
+    let existing: HashMap<String, Task> = db
+        .get_every_task()?
+        .into_iter()
+        .map(|t| (t.id.clone(), t))
+        .collect();
+
+    let mut tasks = Vec::new();
+    let mut new_uids = Vec::new();
+    let mut seen = HashSet::new();
+    for item in items {
+        // Recurring events repeat their UID for each changed occurrence
+        if let Some(uid) = &item.uid {
+            if !seen.insert(uid.clone()) {
+                continue;
+            }
+        }
+        let id = match &item.uid {
+            Some(uid) if existing.contains_key(uid) => Some(uid.clone()),
+            Some(uid) => db.find_external_id(ICS_SOURCE, uid)?,
+            None => None,
+        };
+        match id.and_then(|id| existing.get(&id)) {
+            Some(current) => tasks.push(merge(current, item)),
+            None => {
+                if let Some(uid) = item.uid {
+                    new_uids.push((uid, item.task.id.clone()));
+                }
+                tasks.push(item.task);
+            }
+        }
+    }
+
+    let summary = import_tasks(db, tasks, dry_run)?;
+    if !dry_run {
+        for (uid, task_id) in new_uids {
+            db.set_external_id(ICS_SOURCE, &uid, &task_id)?;
+        }
+    }
+    Ok(summary)
+}
+
+/// `current` with what the calendar knows about it.
+fn merge(current: &Task, item: IcsItem) -> Task {
+    let incoming = item.task;
+    let mut task = current.clone();
+    task.title = incoming.title;
+    task.description = incoming.description;
+    task.priority = incoming.priority;
+    task.due_date = incoming.due_date;
+    if task.status != incoming.status {
+        task.completed_at = incoming.completed_at;
+    } else if incoming.status == TaskStatus::Done {
+        task.completed_at = current.completed_at.or(incoming.completed_at);
+    }
+    task.status = incoming.status;
+    if !incoming.tags.is_empty() {
+        task.tags = incoming.tags;
+    }
+    if incoming.project.is_some() {
+        task.project = incoming.project;
+    }
+    if let Some(xp) = item.xp {
+        task.xp_reward = xp;
+    }
+    task
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar(body: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n",
+            body
+        )
+    }
+
+    fn unfold(folded: &str) -> String {
+        folded.replace("\r\n ", "")
+    }
+
+    #[test]
+    fn folds_long_lines_within_the_limit() {
+        let line = format!("DESCRIPTION:{}", "x".repeat(200));
+        let folded = fold(&line);
+        assert!(folded.split("\r\n").all(|piece| piece.len() <= LINE_LIMIT));
+        assert_eq!(unfold(&folded), line);
+        assert_eq!(fold("SUMMARY:short"), "SUMMARY:short");
+    }
+
+    #[test]
+    fn never_folds_inside_a_character() {
+        let line = format!("SUMMARY:{}", "日本語の説明🎉".repeat(20));
+        let folded = fold(&line);
+        assert!(folded.split("\r\n").all(|piece| piece.len() <= LINE_LIMIT));
+        assert_eq!(unfold(&folded), line);
+    }
+
+    #[test]
+    fn escapes_round_trip() {
+        for text in ["plain", "a, b; c\\d", "two\nlines", "ends with \\"] {
+            assert_eq!(unescape(&escape(text)), text);
+        }
+        assert_eq!(unescape("a\\Nb\\,c"), "a\nb,c");
+        assert_eq!(unescape("dangling\\"), "dangling");
+    }
+
+    #[test]
+    fn reads_todos_and_events() {
+        let ics = calendar(
+            "BEGIN:VTODO\r\n\
+             UID:todo-1\r\n\
+             SUMMARY:Write the rep\r\n \
+             ort\r\n\
+             DESCRIPTION:Line one\\nLine two\\, with comma\r\n\
+             DUE;VALUE=DATE:20261020\r\n\
+             PRIORITY:1\r\n\
+             STATUS:IN-PROCESS\r\n\
+             CATEGORIES:work,urgent\r\n\
+             X-ZENITH-PROJECT:Launch\r\n\
+             X-ZENITH-XP:25\r\n\
+             BEGIN:VALARM\r\n\
+             SUMMARY:Not the title\r\n\
+             END:VALARM\r\n\
+             END:VTODO\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:event-1\r\n\
+             SUMMARY:Standup\r\n\
+             DTSTART;TZID=\"Europe/Berlin\":20261020T090000\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VTODO\r\n\
+             SUMMARY:Dropped\r\n\
+             STATUS:CANCELLED\r\n\
+             END:VTODO\r\n",
+        );
+        let items = read_ics(ics.as_bytes()).unwrap();
+        assert_eq!(items.len(), 2);
+
+        let todo = &items[0];
+        assert_eq!(todo.uid.as_deref(), Some("todo-1"));
+        assert_eq!(todo.task.title, "Write the report");
+        assert_eq!(todo.task.description, "Line one\nLine two, with comma");
+        assert_eq!(todo.task.priority, TaskPriority::High);
+        assert_eq!(todo.task.status, TaskStatus::Doing);
+        assert_eq!(todo.task.tags, ["work", "urgent"]);
+        assert_eq!(todo.task.project.as_deref(), Some("Launch"));
+        assert_eq!(todo.xp, Some(25));
+        let (day, time) = timezone::due_parts(todo.task.due_date.unwrap());
+        assert_eq!(day, NaiveDate::from_ymd_opt(2026, 10, 20).unwrap());
+        assert_eq!(time, timezone::end_of_day());
+
+        let event = &items[1];
+        assert_eq!(event.task.title, "Standup");
+        assert_eq!(
+            event.task.due_date.unwrap().to_rfc3339(),
+            "2026-10-20T07:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn completed_todos_are_done() {
+        let ics =
+            calendar("BEGIN:VTODO\r\nSUMMARY:Done\r\nCOMPLETED:20261018T120000Z\r\nEND:VTODO\r\n");
+        let item = read_ics(ics.as_bytes()).unwrap().remove(0);
+        assert_eq!(item.task.status, TaskStatus::Done);
+        assert_eq!(
+            item.task.completed_at.unwrap().to_rfc3339(),
+            "2026-10-18T12:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn ignores_a_negative_reward() {
+        let ics = calendar("BEGIN:VTODO\r\nSUMMARY:Cheat\r\nX-ZENITH-XP:-50\r\nEND:VTODO\r\n");
+        let item = read_ics(ics.as_bytes()).unwrap().remove(0);
+        assert_eq!(item.xp, None);
+        assert_eq!(item.task.xp_reward, 10);
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_calendars() {
+        assert!(read_ics("BEGIN:VTODO\nSUMMARY:x\nEND:VTODO\n".as_bytes()).is_err());
+        assert!(read_ics("".as_bytes()).is_err());
+    }
+}
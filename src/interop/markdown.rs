@@ -0,0 +1,430 @@
+use crate::db::{
+    models::{Task, TaskPriority, TaskStatus},
+    Database,
+};
+use crate::inputs::quick_add::QuickAdd;
+use color_eyre::eyre::Result;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+const MARKER_START: &str = "<!-- zenith:";
+const MARKER_END: &str = "-->";
+
+#[derive(Default)]
+pub struct SyncSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub completed: usize,
+    pub reopened: usize,
+    /// Lines whose checkbox or marker changed in the file.
+    pub rewritten: usize,
+    pub blocked: Vec<String>,
+}
+
+/// One `- [ ] text <!-- zenith:id -->` line; `prefix` is the indent and
+/// bullet, kept as written.
+struct Item<'a> {
+    prefix: &'a str,
+    done: bool,
+    text: &'a str,
+    id: Option<&'a str>,
+}
+
+fn parse_item(line: &str) -> Option<Item<'_>> {
+    let body = line.trim_start();
+    let indent = line.len() - body.len();
+    let rest = body.strip_prefix(['-', '*', '+'])?.strip_prefix(' ')?;
+    let (done, rest) = match rest.get(..3)? {
+        "[ ]" => (false, &rest[3..]),
+        "[x]" | "[X]" => (true, &rest[3..]),
+        _ => return None,
+    };
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let (text, id) = match rest.find(MARKER_START) {
+        Some(start) => {
+            let marker = &rest[start + MARKER_START.len()..];
+            let id = marker.find(MARKER_END).map(|end| marker[..end].trim());
+            (rest[..start].trim(), id.filter(|id| !id.is_empty()))
+        }
+        None => (rest.trim(), None),
+    };
+    Some(Item {
+        prefix: &line[..indent + 2],
+        done,
+        text,
+        id,
+    })
+}
+
+fn render(item: &Item, done: bool, id: &str) -> String {
+    format!(
+        "{}[{}] {} {}{} {}",
+        item.prefix,
+        if done { 'x' } else { ' ' },
+        item.text,
+        MARKER_START,
+        id,
+        MARKER_END
+    )
+}
+
+/// Syncs the checklist in `contents`, the file at `path`, both ways and
+/// returns the file as it should now read. Items are parsed like quick-add
+/// (`!h`, `due:fri`, `#tag`, `@project`). Compared with the last sync, an
+/// edited line updates its task and a ticked or unticked box completes or
+/// reopens it; otherwise the task's status is written back to the box. New
+/// items become tasks and get a marker, ticked ones completed like any other;
+/// lines inside code fences are left alone.
+pub fn sync(db: &Database, path: &str, contents: &str) -> Result<(String, SyncSummary)> {
+    let tasks: HashMap<String, Task> = db
+        .get_every_task()?
+        .into_iter()
+        .map(|t| (t.id.clone(), t))
+        .collect();
+    let mut last_sync = db.get_markdown_sync(path)?;
+    let mut seen = HashSet::new();
+    let mut summary = SyncSummary::default();
+    let mut lines = Vec::new();
+    let mut in_code = false;
+
+    for line in contents.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        let item = match parse_item(line) {
+            Some(item) if !in_code => item,
+            _ => {
+                lines.push(line.to_string());
+                continue;
+            }
+        };
+        let parsed = QuickAdd::parse(item.text);
+        if parsed.title.is_empty() {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        // A copied line repeats its marker; the copy becomes a new task
+        let linked = item
+            .id
+            .filter(|id| seen.insert(id.to_string()))
+            .and_then(|id| tasks.get(id));
+        let (id, done) = match linked {
+            // Trashed tasks keep their line as it is until they're restored
+            Some(task) if task.deleted_at.is_some() => {
+                last_sync.remove(&task.id);
+                lines.push(line.to_string());
+                continue;
+            }
+            Some(task) => {
+                let last = last_sync.remove(&task.id);
+                let done = sync_item(db, task, &item, parsed, last, &mut summary)?;
+                (task.id.clone(), done)
+            }
+            None => {
+                let mut task = Task::new(
+                    parsed.title,
+                    String::new(),
+                    parsed.priority.unwrap_or(TaskPriority::Medium),
+                    10,
+                    parsed.due_date,
+                );
+                task.project = parsed.project;
+                task.tags = parsed.tags;
+                db.create_task(&task)?;
+                summary.created += 1;
+                let done = item.done && db.complete_task(&task)?;
+                if done {
+                    summary.completed += 1;
+                }
+                (task.id, done)
+            }
+        };
+
+        let synced = render(&item, done, &id);
+        if synced != line {
+            summary.rewritten += 1;
+        }
+        db.set_markdown_sync(path, &id, item.text, done)?;
+        lines.push(synced);
+    }
+
+    // Lines removed from the file unlink their task but don't delete it
+    for task_id in last_sync.keys() {
+        db.remove_markdown_sync(path, task_id)?;
+    }
+
+    let newline = if contents.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut synced = lines.join(newline);
+    if contents.ends_with('\n') {
+        synced.push_str(newline);
+    }
+    Ok((synced, summary))
+}
+
+/// Applies the file's side of one linked item and returns whether the task
+/// ends up done. `last` is the line's text and box as of the last sync;
+/// without one the line wins for the text and the task for the status.
+///
+/// An edited line always sets the title, but a token only sets its field
+/// when it's on the line and differs from the last sync. Fixing a typo keeps
+/// what was set in the app, and an unchanged `due:fri` isn't re-read as
+/// the next Friday. Removing a token leaves its field alone.
+fn sync_item(
+    db: &Database,
+    task: &Task,
+    item: &Item,
+    parsed: QuickAdd,
+    last: Option<(String, bool)>,
+    summary: &mut SyncSummary,
+) -> Result<bool> {
+    if last.as_ref().is_none_or(|(text, _)| text != item.text) {
+        let before = last
+            .as_ref()
+            .map(|(text, _)| QuickAdd::parse(text))
+            .unwrap_or_default();
+        let priority = parsed
+            .priority
+            .filter(|p| Some(*p) != before.priority)
+            .unwrap_or(task.priority);
+        let due_date = parsed
+            .due_date
+            .filter(|d| Some(*d) != before.due_date)
+            .or(task.due_date);
+        let project = parsed
+            .project
+            .clone()
+            .filter(|p| Some(p) != before.project.as_ref())
+            .or(task.project.clone());
+        let mut tags = if parsed.tags.is_empty() || parsed.tags == before.tags {
+            task.tags.clone()
+        } else {
+            parsed.tags.clone()
+        };
+        let mut current_tags = task.tags.clone();
+        tags.sort();
+        current_tags.sort();
+        if task.title != parsed.title
+            || task.priority != priority
+            || task.due_date != due_date
+            || task.project != project
+            || current_tags != tags
+        {
+            db.update_task_content(
+                &task.id,
+                &parsed.title,
+                &task.description,
+                priority,
+                task.xp_reward,
+                due_date,
+                task.recurrence.as_ref(),
+            )?;
+            db.set_task_labels(&task.id, project.as_deref(), &tags)?;
+            summary.updated += 1;
+        }
+    }
+
+    let done = task.status == TaskStatus::Done;
+    let ticked = last.is_some_and(|(_, was_done)| was_done != item.done);
+    if !ticked || item.done == done {
+        return Ok(done);
+    }
+    if !item.done {
+        db.update_task_status(&task.id, TaskStatus::Todo)?;
+        summary.reopened += 1;
+        Ok(false)
+    } else if db.complete_task(task)? {
+        summary.completed += 1;
+        Ok(true)
+    } else {
+        summary.blocked.push(parsed.title);
+        Ok(false)
+    }
+}
+
+impl fmt::Display for SyncSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Sync summary:")?;
+        writeln!(f, "  create:    {}", self.created)?;
+        writeln!(f, "  update:    {}", self.updated)?;
+        writeln!(f, "  complete:  {}", self.completed)?;
+        writeln!(f, "  reopen:    {}", self.reopened)?;
+        write!(f, "  rewritten: {} line(s)", self.rewritten)?;
+        for title in &self.blocked {
+            write!(
+                f,
+                "\n    ! {}: blocked by an unfinished task, left open",
+                title
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "/notes/todo.md";
+
+    fn item(line: &str) -> Option<(&str, bool, &str, Option<&str>)> {
+        parse_item(line).map(|i| (i.prefix, i.done, i.text, i.id))
+    }
+
+    /// The id in the marker of line `n`.
+    fn marker_id(contents: &str, n: usize) -> String {
+        parse_item(contents.lines().nth(n).unwrap())
+            .and_then(|i| i.id)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn parses_checklist_lines() {
+        assert_eq!(
+            item("- [ ] Buy milk"),
+            Some(("- ", false, "Buy milk", None))
+        );
+        assert_eq!(
+            item("    * [x] Done  <!-- zenith:1a2b3c4d -->"),
+            Some(("    * ", true, "Done", Some("1a2b3c4d")))
+        );
+        assert_eq!(item("+ [X] Upper"), Some(("+ ", true, "Upper", None)));
+        assert_eq!(
+            item("- [ ] Empty marker <!-- zenith: -->"),
+            Some(("- ", false, "Empty marker", None))
+        );
+        assert_eq!(item("- [ ]"), Some(("- ", false, "", None)));
+    }
+
+    #[test]
+    fn ignores_other_lines() {
+        for line in [
+            "",
+            "# Heading",
+            "Some text - [ ] inline",
+            "-[ ] no space",
+            "- [ ]no space after box",
+            "- [y] odd box",
+            "- plain bullet",
+            "1. [ ] numbered",
+        ] {
+            assert!(parse_item(line).is_none(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn creates_tasks_for_new_items() {
+        let db = Database::open_in_memory().unwrap();
+        let contents = "# Today\n- [ ] Buy milk !h #home\n```\n- [ ] not a task\n```\n";
+        let (synced, summary) = sync(&db, PATH, contents).unwrap();
+        assert_eq!(summary.created, 1);
+        assert_eq!(summary.rewritten, 1);
+
+        let id = marker_id(&synced, 1);
+        let task = db.get_task(&id).unwrap().unwrap();
+        assert_eq!(task.title, "Buy milk");
+        assert_eq!(task.priority, TaskPriority::High);
+        assert_eq!(task.tags, ["home"]);
+        assert_eq!(synced.lines().nth(3), Some("- [ ] not a task"));
+        assert!(synced.ends_with('\n'));
+
+        // Nothing changed on either side
+        let (again, summary) = sync(&db, PATH, &synced).unwrap();
+        assert_eq!(again, synced);
+        assert_eq!(summary.created + summary.updated + summary.rewritten, 0);
+    }
+
+    #[test]
+    fn ticking_a_box_completes_the_task() {
+        let db = Database::open_in_memory().unwrap();
+        let (synced, _) = sync(&db, PATH, "- [ ] Write report\n").unwrap();
+        let id = marker_id(&synced, 0);
+
+        let ticked = synced.replace("[ ]", "[x]");
+        let (_, summary) = sync(&db, PATH, &ticked).unwrap();
+        assert_eq!(summary.completed, 1);
+        assert_eq!(db.get_task(&id).unwrap().unwrap().status, TaskStatus::Done);
+        assert_eq!(db.get_user_profile().unwrap().current_xp, 10);
+
+        // Reopened in the app, the box is cleared on the next sync
+        db.update_task_status(&id, TaskStatus::Todo).unwrap();
+        let (synced, summary) = sync(&db, PATH, &ticked).unwrap();
+        assert_eq!(summary.reopened, 0);
+        assert!(synced.starts_with("- [ ] Write report"));
+    }
+
+    #[test]
+    fn blocked_tasks_stay_open() {
+        let db = Database::open_in_memory().unwrap();
+        let (synced, _) = sync(&db, PATH, "- [ ] Blocker\n- [ ] Blocked\n").unwrap();
+        let (blocker, blocked) = (marker_id(&synced, 0), marker_id(&synced, 1));
+        db.add_dependency(&blocked, &blocker).unwrap();
+
+        let ticked = synced.replacen("[ ]", "[x]", 2).replacen("[x]", "[ ]", 1);
+        let (synced, summary) = sync(&db, PATH, &ticked).unwrap();
+        assert_eq!(summary.blocked, ["Blocked"]);
+        assert_eq!(summary.completed, 0);
+        assert!(synced.lines().nth(1).unwrap().starts_with("- [ ] Blocked"));
+        assert_eq!(
+            db.get_task(&blocked).unwrap().unwrap().status,
+            TaskStatus::Todo
+        );
+    }
+
+    #[test]
+    fn edited_lines_update_and_copies_become_new_tasks() {
+        let db = Database::open_in_memory().unwrap();
+        let (synced, _) = sync(&db, PATH, "- [ ] Draft\r\n").unwrap();
+        let id = marker_id(&synced, 0);
+        assert!(synced.ends_with("\r\n"));
+
+        let edited = synced.replace("Draft", "Draft the talk @conf");
+        let copied = format!("{}{}", edited, edited);
+        let (synced, summary) = sync(&db, PATH, &copied).unwrap();
+        assert_eq!(summary.updated, 1);
+        assert_eq!(summary.created, 1);
+        assert_ne!(marker_id(&synced, 1), id);
+
+        let task = db.get_task(&id).unwrap().unwrap();
+        assert_eq!(task.title, "Draft the talk");
+        assert_eq!(task.project.as_deref(), Some("conf"));
+    }
+
+    #[test]
+    fn editing_the_text_keeps_what_the_app_set() {
+        let db = Database::open_in_memory().unwrap();
+        let (synced, _) = sync(&db, PATH, "- [ ] Wirte report due:fri\n").unwrap();
+        let id = marker_id(&synced, 0);
+
+        let due = crate::utils::parse_due_date("2030-01-15");
+        let task = db.get_task(&id).unwrap().unwrap();
+        db.update_task_content(&id, &task.title, "", TaskPriority::High, 10, due, None)
+            .unwrap();
+
+        let fixed = synced.replace("Wirte", "Write");
+        let (_, summary) = sync(&db, PATH, &fixed).unwrap();
+        assert_eq!(summary.updated, 1);
+        let task = db.get_task(&id).unwrap().unwrap();
+        assert_eq!(task.title, "Write report");
+        assert_eq!(task.due_date, due);
+        assert_eq!(task.priority, TaskPriority::High);
+    }
+
+    #[test]
+    fn new_ticked_items_are_completed_with_their_xp() {
+        let db = Database::open_in_memory().unwrap();
+        let (synced, summary) = sync(&db, PATH, "- [x] Already done\n").unwrap();
+        assert_eq!((summary.created, summary.completed), (1, 1));
+        let task = db.get_task(&marker_id(&synced, 0)).unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Done);
+        assert_eq!(db.get_user_profile().unwrap().current_xp, 10);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::db::{creates_cycle, models::Task, Database};
+use crate::utils::short_id;
+use color_eyre::eyre::Result;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+pub mod backup;
+pub mod git;
+pub mod ics;
+pub mod markdown;
+pub mod taskwarrior;
+pub mod todotxt;
+
+#[derive(Default)]
+pub struct ImportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub conflicts: Vec<(Task, &'static str)>,
+    /// `(task, blocker, reason)` dependencies left out because the blocker
+    /// isn't there or the edge would close a cycle.
+    pub dropped_dependencies: Vec<(Task, String, &'static str)>,
+    pub dry_run: bool,
+}
+
+/// Upserts `incoming` by task id. A task whose id already exists with a
+/// different creation time is treated as a different task and skipped, as
+/// is one whose id isn't usable (see `valid_id`) or whose XP reward is
+/// negative. Dependencies on a task
+/// that is neither in the database nor imported alongside (a skipped task,
+/// or one left in the Trash at export) are dropped, as are those that would
+/// close a cycle, the same check `Database::add_dependency` makes.
+pub fn import_tasks(db: &Database, incoming: Vec<Task>, dry_run: bool) -> Result<ImportSummary> {
+    let existing: HashMap<String, Task> = db
+        .get_every_task()?
+        .into_iter()
+        .map(|t| (t.id.clone(), t))
+        .collect();
+    let mut graph: HashMap<String, Vec<String>> = existing
+        .values()
+        .map(|t| (t.id.clone(), t.blocked_by.clone()))
+        .collect();
+
+    let mut summary = ImportSummary {
+        dry_run,
+        ..Default::default()
+    };
+
+    let mut writes = Vec::new();
+    for task in incoming {
+        if !valid_id(&task.id) {
+            summary
+                .conflicts
+                .push((task, "id must be a UUID or other printable ASCII"));
+            continue;
+        }
+        if task.xp_reward < 0 {
+            summary.conflicts.push((task, "negative XP reward"));
+            continue;
+        }
+        match existing.get(&task.id) {
+            None => summary.created += 1,
+            Some(current) if *current == task => {
+                summary.unchanged += 1;
+                continue;
+            }
+            Some(current) if current.created_at != task.created_at => {
+                summary
+                    .conflicts
+                    .push((task, "id exists with a different creation date"));
+                continue;
+            }
+            Some(_) => summary.updated += 1,
+        }
+        writes.push(task);
+    }
+
+    let written: HashSet<String> = writes.iter().map(|t| t.id.clone()).collect();
+    for task in &mut writes {
+        // The task's own edges are replaced, so only the others can loop back
+        graph.insert(task.id.clone(), Vec::new());
+        let mut blocked_by = Vec::new();
+        for blocker in std::mem::take(&mut task.blocked_by) {
+            if !existing.contains_key(&blocker) && !written.contains(&blocker) {
+                summary
+                    .dropped_dependencies
+                    .push((task.clone(), blocker, "no such task"));
+            } else if creates_cycle(&task.id, &blocker, |id| {
+                Ok(graph.get(id).cloned().unwrap_or_default())
+            })? {
+                summary.dropped_dependencies.push((
+                    task.clone(),
+                    blocker,
+                    "it would create a cycle",
+                ));
+            } else {
+                blocked_by.push(blocker);
+            }
+        }
+        graph.insert(task.id.clone(), blocked_by.clone());
+        task.blocked_by = blocked_by;
+    }
+
+    if !dry_run {
+        db.upsert_tasks(&writes)?;
+    }
+    Ok(summary)
+}
+
+/// Ids from other tools are kept as they are, but only printable ASCII (a
+/// UUID, a Taskwarrior uuid, ...) so prefixes can be typed and shown.
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_graphic())
+}
+
+impl fmt::Display for ImportSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = if self.dry_run {
+            "Import summary (dry run, nothing written):"
+        } else {
+            "Import summary:"
+        };
+        writeln!(f, "{}", header)?;
+        writeln!(f, "  create:    {}", self.created)?;
+        writeln!(f, "  update:    {}", self.updated)?;
+        writeln!(f, "  unchanged: {}", self.unchanged)?;
+        write!(f, "  conflict:  {}", self.conflicts.len())?;
+        for (task, reason) in &self.conflicts {
+            write!(
+                f,
+                "\n    ! {} {}: {}",
+                short_id(&task.id),
+                task.title,
+                reason
+            )?;
+        }
+        for (task, blocker, reason) in &self.dropped_dependencies {
+            write!(
+                f,
+                "\n    ~ {} {}: dropped dependency on {}, {}",
+                short_id(&task.id),
+                task.title,
+                short_id(blocker),
+                reason
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::TaskPriority;
+
+    fn task(title: &str) -> Task {
+        Task::new(title.into(), String::new(), TaskPriority::Medium, 10, None)
+    }
+
+    #[test]
+    fn drops_imported_dependencies_that_close_a_cycle() {
+        let db = Database::open_in_memory().unwrap();
+        let (a, b, mut c) = (task("A"), task("B"), task("C"));
+        db.create_task(&a).unwrap();
+        db.create_task(&b).unwrap();
+        db.add_dependency(&b.id, &a.id).unwrap();
+
+        // A waits on C, and C on B and on itself: C -> B -> A -> C
+        let mut a = db.get_task(&a.id).unwrap().unwrap();
+        a.blocked_by = vec![c.id.clone()];
+        c.blocked_by = vec![b.id.clone(), c.id.clone()];
+        let summary = import_tasks(&db, vec![a.clone(), c.clone()], false).unwrap();
+
+        let dropped: Vec<_> = summary
+            .dropped_dependencies
+            .iter()
+            .map(|(task, blocker, _)| (task.title.as_str(), blocker.as_str()))
+            .collect();
+        assert_eq!(dropped, [("C", b.id.as_str()), ("C", c.id.as_str())]);
+        assert_eq!(
+            db.get_task(&a.id).unwrap().unwrap().blocked_by,
+            [c.id.clone()]
+        );
+        assert!(db.get_task(&c.id).unwrap().unwrap().blocked_by.is_empty());
+    }
+
+    #[test]
+    fn skips_unusable_ids_and_negative_rewards() {
+        let db = Database::open_in_memory().unwrap();
+        let mut odd = task("Odd id");
+        odd.id = "has space".into();
+        let mut cheat = task("Cheat");
+        cheat.xp_reward = -1000;
+        let summary = import_tasks(&db, vec![odd, cheat.clone(), task("Fair")], false).unwrap();
+
+        assert_eq!(summary.created, 1);
+        let reasons: Vec<_> = summary.conflicts.iter().map(|(_, r)| *r).collect();
+        assert_eq!(
+            reasons,
+            [
+                "id must be a UUID or other printable ASCII",
+                "negative XP reward"
+            ]
+        );
+        assert!(db.get_task(&cheat.id).unwrap().is_none());
+        assert!(db.get_task("has space").unwrap().is_none());
+    }
+
+    #[test]
+    fn drops_dependencies_on_tasks_left_in_the_trash() {
+        let source = Database::open_in_memory().unwrap();
+        let (blocker, waiting) = (task("Blocker"), task("Waiting"));
+        source.create_task(&blocker).unwrap();
+        source.create_task(&waiting).unwrap();
+        source.add_dependency(&waiting.id, &blocker.id).unwrap();
+        source.delete_task(&blocker.id).unwrap();
+
+        let mut json = Vec::new();
+        backup::write_json(&source, &mut json).unwrap();
+        let snapshot = backup::read_json(json.as_slice()).unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        let dry = import_tasks(&db, snapshot.tasks.clone(), true).unwrap();
+        assert_eq!(dry.dropped_dependencies.len(), 1);
+        assert_eq!(dry.dropped_dependencies[0].1, blocker.id);
+
+        let summary = import_tasks(&db, snapshot.tasks, false).unwrap();
+        assert_eq!(summary.created, 1);
+        assert!(db
+            .get_task(&waiting.id)
+            .unwrap()
+            .unwrap()
+            .blocked_by
+            .is_empty());
+    }
+}
@@ -0,0 +1,278 @@
+use crate::db::{
+    models::{Task, TaskPriority, TaskStatus},
+    Database,
+};
+use crate::inputs::quick_add::parse_tags;
+use crate::interop::{import_tasks, ImportSummary};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+/// One task as `task export` writes it. Fields we don't map (urgency,
+/// recurrence, UDAs) are ignored on import.
+#[derive(Serialize, Deserialize)]
+struct TwTask {
+    uuid: String,
+    description: String,
+    status: String,
+    entry: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<TwAnnotation>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TwAnnotation {
+    entry: String,
+    description: String,
+}
+
+fn tw_time(t: DateTime<Utc>) -> String {
+    t.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn parse_tw_time(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+impl From<&Task> for TwTask {
+    fn from(t: &Task) -> Self {
+        Self {
+            uuid: t.id.clone(),
+            description: t.title.clone(),
+            status: match t.status {
+                TaskStatus::Done => "completed",
+                _ => "pending",
+            }
+            .to_string(),
+            entry: tw_time(t.created_at),
+            // Zenith doesn't record when work started, only that it has
+            start: (t.status == TaskStatus::Doing).then(|| tw_time(t.created_at)),
+            end: t.completed_at.map(tw_time),
+            due: t.due_date.map(tw_time),
+            priority: Some(
+                match t.priority {
+                    TaskPriority::High => "H",
+                    TaskPriority::Medium => "M",
+                    TaskPriority::Low => "L",
+                }
+                .to_string(),
+            ),
+            project: t.project.clone(),
+            tags: t.tags.clone(),
+            annotations: t
+                .description
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(|l| TwAnnotation {
+                    entry: tw_time(t.created_at),
+                    description: l.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A JSON array in the format `task import` reads.
+pub fn write_taskwarrior(db: &Database, mut out: impl Write) -> Result<()> {
+    let tasks: Vec<TwTask> = db
+        .get_exportable_tasks()?
+        .iter()
+        .map(TwTask::from)
+        .collect();
+    serde_json::to_writer_pretty(&mut out, &tasks)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Reads `task export` output: a JSON array, or one object per line as
+/// older versions wrote it. Deleted tasks and recurrence templates are
+/// skipped.
+pub fn read_taskwarrior(mut input: impl Read) -> Result<Vec<Task>> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let raw: Vec<TwTask> = if text.trim_start().starts_with('[') {
+        serde_json::from_str(&text)?
+    } else {
+        text.lines()
+            .map(|l| l.trim().trim_end_matches(','))
+            .filter(|l| !l.is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?
+    };
+
+    let mut tasks = Vec::new();
+    for tw in raw {
+        let status = match tw.status.as_str() {
+            "completed" => TaskStatus::Done,
+            "pending" | "waiting" if tw.start.is_some() => TaskStatus::Doing,
+            "pending" | "waiting" => TaskStatus::Todo,
+            _ => continue,
+        };
+        let priority = match tw.priority.as_deref() {
+            Some("H") => TaskPriority::High,
+            Some("L") => TaskPriority::Low,
+            _ => TaskPriority::Medium,
+        };
+        let description = tw
+            .annotations
+            .iter()
+            .map(|a| a.description.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut task = Task::new(
+            tw.description,
+            description,
+            priority,
+            10,
+            tw.due.as_deref().and_then(parse_tw_time),
+        );
+        task.id = tw.uuid;
+        task.created_at = parse_tw_time(&tw.entry).unwrap_or(task.created_at);
+        if status == TaskStatus::Done {
+            task.completed_at = Some(
+                tw.end
+                    .as_deref()
+                    .and_then(parse_tw_time)
+                    .unwrap_or_else(Utc::now),
+            );
+        }
+        task.status = status;
+        task.project = tw.project;
+        task.tags = parse_tags(&tw.tags.join(","));
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+/// Upserts by Taskwarrior UUID, which is also the task id, so our own
+/// exports round-trip. Fields Taskwarrior doesn't have are kept.
+pub fn import_taskwarrior(
+    db: &Database,
+    incoming: Vec<Task>,
+    dry_run: bool,
+) -> Result<ImportSummary> {
+    let existing: HashMap<String, Task> = db
+        .get_every_task()?
+        .into_iter()
+        .map(|t| (t.id.clone(), t))
+        .collect();
+
+    let tasks = incoming
+        .into_iter()
+        .map(|task| match existing.get(&task.id) {
+            Some(current) => merge(current, task),
+            None => task,
+        })
+        .collect();
+    import_tasks(db, tasks, dry_run)
+}
+
+/// `current` with what Taskwarrior knows about it. Timestamps only have
+/// second precision there, so ours win when they agree to the second.
+fn merge(current: &Task, incoming: Task) -> Task {
+    let same_second = |a: DateTime<Utc>, b: DateTime<Utc>| a.timestamp() == b.timestamp();
+    let mut task = current.clone();
+    task.title = incoming.title;
+    task.description = incoming.description;
+    task.priority = incoming.priority;
+    if !matches!((current.due_date, incoming.due_date), (Some(a), Some(b)) if same_second(a, b)) {
+        task.due_date = incoming.due_date;
+    }
+    if !same_second(current.created_at, incoming.created_at) {
+        task.created_at = incoming.created_at;
+    }
+    if task.status != incoming.status {
+        task.completed_at = incoming.completed_at;
+    } else if incoming.status == TaskStatus::Done {
+        task.completed_at = current.completed_at.or(incoming.completed_at);
+    }
+    task.status = incoming.status;
+    task.project = incoming.project;
+    task.tags = incoming.tags;
+    task
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_an_export_array() {
+        let json = r#"[
+            {"uuid":"6fd0b4b2-5f4b-4a4f-9d55-0b1e5a3b1c01","description":"Write docs","status":"pending",
+             "entry":"20261001T080000Z","start":"20261002T090000Z","due":"20261020T170000Z",
+             "priority":"H","project":"zenith","tags":["Docs","writing"],
+             "annotations":[{"entry":"20261001T080000Z","description":"first"},
+                            {"entry":"20261001T080000Z","description":"second"}],
+             "urgency":12.5},
+            {"uuid":"6fd0b4b2-5f4b-4a4f-9d55-0b1e5a3b1c02","description":"Ship","status":"completed",
+             "entry":"20261001T080000Z","end":"20261017T120000Z","priority":"L"},
+            {"uuid":"6fd0b4b2-5f4b-4a4f-9d55-0b1e5a3b1c03","description":"Gone","status":"deleted",
+             "entry":"20261001T080000Z"},
+            {"uuid":"6fd0b4b2-5f4b-4a4f-9d55-0b1e5a3b1c04","description":"Template","status":"recurring",
+             "entry":"20261001T080000Z"}
+        ]"#;
+        let tasks = read_taskwarrior(json.as_bytes()).unwrap();
+        assert_eq!(tasks.len(), 2);
+
+        let docs = &tasks[0];
+        assert_eq!(docs.id, "6fd0b4b2-5f4b-4a4f-9d55-0b1e5a3b1c01");
+        assert_eq!(docs.title, "Write docs");
+        assert_eq!(docs.description, "first\nsecond");
+        assert_eq!(docs.status, TaskStatus::Doing);
+        assert_eq!(docs.priority, TaskPriority::High);
+        assert_eq!(docs.project.as_deref(), Some("zenith"));
+        assert_eq!(docs.tags, ["docs", "writing"]);
+        assert_eq!(
+            docs.due_date.unwrap().to_rfc3339(),
+            "2026-10-20T17:00:00+00:00"
+        );
+        assert_eq!(docs.created_at.to_rfc3339(), "2026-10-01T08:00:00+00:00");
+
+        let ship = &tasks[1];
+        assert_eq!(ship.status, TaskStatus::Done);
+        assert_eq!(ship.priority, TaskPriority::Low);
+        assert_eq!(
+            ship.completed_at.unwrap().to_rfc3339(),
+            "2026-10-17T12:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn reads_one_object_per_line() {
+        let lines = concat!(
+            r#"{"uuid":"a1","description":"Waiting task","status":"waiting","entry":"20261001T080000Z"},"#,
+            "\n\n",
+            r#"{"uuid":"a2","description":"Bad entry","status":"pending","entry":"bad date"}"#,
+            "\n",
+        );
+        let tasks = read_taskwarrior(lines.as_bytes()).unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].title, "Waiting task");
+        assert_eq!(tasks[0].status, TaskStatus::Todo);
+        assert_eq!(tasks[1].title, "Bad entry");
+    }
+
+    #[test]
+    fn rejects_broken_json() {
+        assert!(read_taskwarrior("[{\"uuid\":\"x\"".as_bytes()).is_err());
+        assert!(read_taskwarrior("{\"description\":\"no uuid\"}".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,328 @@
+use crate::db::{
+    models::{Task, TaskPriority, TaskStatus},
+    Database,
+};
+use crate::inputs::quick_add::parse_tags;
+use crate::interop::{import_tasks, ImportSummary};
+use crate::utils::timezone;
+use chrono::{Datelike, NaiveDate, NaiveTime, Utc};
+use color_eyre::eyre::Result;
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, Write};
+
+/// One line per task:
+/// `x 2026-10-17 2026-10-01 Title +project @tag due:2026-10-20 pri:A`.
+/// Done tasks carry their priority as `pri:` as the format suggests, since
+/// `(A)` may not follow the `x`. Spaces in a project become `-`.
+pub fn write_todotxt(db: &Database, mut out: impl Write) -> Result<()> {
+    for task in db.get_exportable_tasks()? {
+        writeln!(out, "{}", format_line(&task))?;
+    }
+    Ok(())
+}
+
+/// The title as it appears on a line: whitespace runs become one space.
+fn flatten(title: &str) -> String {
+    title.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The project as a single `+project` word: whitespace runs become `-`.
+fn project_word(project: &str) -> String {
+    project.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+fn format_line(task: &Task) -> String {
+    let mut parts = Vec::new();
+    let priority = match task.priority {
+        TaskPriority::High => "A",
+        TaskPriority::Medium => "B",
+        TaskPriority::Low => "C",
+    };
+    if task.status == TaskStatus::Done {
+        parts.push("x".to_string());
+        if let Some(completed) = task.completed_at {
+            parts.push(timezone::local(completed).format("%Y-%m-%d").to_string());
+        }
+    } else {
+        parts.push(format!("({})", priority));
+    }
+    parts.push(
+        timezone::local(task.created_at)
+            .format("%Y-%m-%d")
+            .to_string(),
+    );
+    // Lines are the unit of the format, so a multi-line title is flattened
+    parts.push(flatten(&task.title));
+    if let Some(project) = &task.project {
+        parts.push(format!("+{}", project_word(project)));
+    }
+    parts.extend(task.tags.iter().map(|tag| format!("@{}", tag)));
+    if let Some(due) = task.due_date {
+        let (day, _) = timezone::due_parts(due);
+        parts.push(format!("due:{}", day.format("%Y-%m-%d")));
+    }
+    if task.status == TaskStatus::Done {
+        parts.push(format!("pri:{}", priority));
+    }
+    parts.join(" ")
+}
+
+/// Parses a todo.txt file. `(A)` is High, `(B)` Medium and anything lower
+/// Low; the first `+project` is the project, further projects and every
+/// `@context` become tags. Unknown `key:value` pairs stay in the title.
+pub fn read_todotxt(input: impl BufRead) -> Result<Vec<Task>> {
+    let mut tasks = Vec::new();
+    for line in input.lines() {
+        if let Some(task) = parse_line(&line?) {
+            tasks.push(task);
+        }
+    }
+    Ok(tasks)
+}
+
+fn parse_line(line: &str) -> Option<Task> {
+    let mut words = line.split_whitespace().peekable();
+    words.peek()?;
+
+    let done = words.next_if_eq(&"x").is_some();
+    let mut priority = words.next_if(|w| is_priority(w)).map(|w| w.as_bytes()[1]);
+    let first = words
+        .next_if(|w| parse_day(w).is_some())
+        .and_then(parse_day);
+    // A done task's first date is its completion date, its creation date
+    // comes second
+    let (completed, created) = if done {
+        let second = words
+            .next_if(|w| parse_day(w).is_some())
+            .and_then(parse_day);
+        (first, second)
+    } else {
+        (None, first)
+    };
+
+    let mut title = Vec::new();
+    let mut project = None;
+    let mut tags = Vec::new();
+    let mut due = None;
+    for word in words {
+        if let Some(name) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+            match project {
+                None => project = Some(name.to_string()),
+                Some(_) => tags.push(name),
+            }
+        } else if let Some(context) = word.strip_prefix('@').filter(|c| !c.is_empty()) {
+            tags.push(context);
+        } else if let Some(day) = word.strip_prefix("due:").and_then(parse_day) {
+            due = Some(day);
+        } else if let Some(p) = word.strip_prefix("pri:").filter(|p| p.len() == 1) {
+            priority = Some(p.as_bytes()[0].to_ascii_uppercase());
+        } else {
+            title.push(word);
+        }
+    }
+    if title.is_empty() {
+        return None;
+    }
+
+    let priority = match priority {
+        Some(b'A') => TaskPriority::High,
+        Some(b'B') | None => TaskPriority::Medium,
+        Some(_) => TaskPriority::Low,
+    };
+    let due_date = due.map(|day| timezone::to_utc(day.and_time(timezone::end_of_day())));
+    let mut task = Task::new(title.join(" "), String::new(), priority, 10, due_date);
+    if let Some(day) = created {
+        task.created_at = timezone::to_utc(day.and_time(NaiveTime::MIN));
+    }
+    if done {
+        task.status = TaskStatus::Done;
+        task.completed_at = Some(
+            completed
+                .map(|day| timezone::to_utc(day.and_time(timezone::end_of_day())))
+                .unwrap_or_else(Utc::now),
+        );
+    }
+    task.project = project;
+    task.tags = parse_tags(&tags.join(","));
+    Some(task)
+}
+
+fn is_priority(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    bytes.len() == 3 && bytes[0] == b'(' && bytes[1].is_ascii_uppercase() && bytes[2] == b')'
+}
+
+/// `2026-10-20`; `%Y` also takes signed years like `+29000`, which
+/// timestamps can't be stored with.
+fn parse_day(word: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d")
+        .ok()
+        .filter(|day| (1..=9999).contains(&day.year()))
+}
+
+/// todo.txt has no ids, so a line updates a task with the same (flattened)
+/// title and anything else creates a new one; repeated titles pair up in
+/// order.
+/// Descriptions, checklists, XP and the like are kept, as are times of day
+/// that a date-only line can't express.
+pub fn import_todotxt(db: &Database, incoming: Vec<Task>, dry_run: bool) -> Result<ImportSummary> {
+    let mut existing: HashMap<String, VecDeque<Task>> = HashMap::new();
+    for task in db.get_every_task()? {
+        if task.deleted_at.is_none() {
+            existing
+                .entry(flatten(&task.title))
+                .or_default()
+                .push_back(task);
+        }
+    }
+
+    let tasks = incoming
+        .into_iter()
+        .map(|task| {
+            match existing
+                .get_mut(&flatten(&task.title))
+                .and_then(VecDeque::pop_front)
+            {
+                Some(current) => merge(&current, task),
+                None => task,
+            }
+        })
+        .collect();
+    import_tasks(db, tasks, dry_run)
+}
+
+fn merge(current: &Task, incoming: Task) -> Task {
+    let local_day = |t| timezone::local(t).date();
+    let mut task = current.clone();
+    task.priority = incoming.priority;
+    let same_due = match (current.due_date, incoming.due_date) {
+        (Some(a), Some(b)) => timezone::due_parts(a).0 == timezone::due_parts(b).0,
+        (a, b) => a.is_none() && b.is_none(),
+    };
+    if !same_due {
+        task.due_date = incoming.due_date;
+    }
+    if task.status != incoming.status {
+        task.completed_at = incoming.completed_at;
+    } else if let (Some(a), Some(b)) = (current.completed_at, incoming.completed_at) {
+        if local_day(a) != local_day(b) {
+            task.completed_at = incoming.completed_at;
+        }
+    }
+    // Doing has no todo.txt equivalent, so an open line leaves it alone
+    if !(current.status == TaskStatus::Doing && incoming.status == TaskStatus::Todo) {
+        task.status = incoming.status;
+    }
+    if task.status == TaskStatus::Doing {
+        task.completed_at = None;
+    }
+    // A project with spaces comes back dashed; that's still the same one
+    if current.project.as_deref().map(project_word) != incoming.project {
+        task.project = incoming.project;
+    }
+    task.tags = incoming.tags;
+    task
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_open_lines() {
+        let task = parse_line("(A) 2026-10-01 Call mom +family +phone @home due:2026-10-20 url:x")
+            .unwrap();
+        assert_eq!(task.title, "Call mom url:x");
+        assert_eq!(task.priority, TaskPriority::High);
+        assert_eq!(task.status, TaskStatus::Todo);
+        assert_eq!(task.project.as_deref(), Some("family"));
+        assert_eq!(task.tags, ["phone", "home"]);
+        assert_eq!(timezone::local(task.created_at).date(), day("2026-10-01"));
+        assert_eq!(
+            timezone::due_parts(task.due_date.unwrap()),
+            (day("2026-10-20"), timezone::end_of_day())
+        );
+    }
+
+    #[test]
+    fn parses_done_lines() {
+        let task = parse_line("x 2026-10-17 2026-10-01 Ship it pri:c").unwrap();
+        assert_eq!(task.title, "Ship it");
+        assert_eq!(task.status, TaskStatus::Done);
+        assert_eq!(task.priority, TaskPriority::Low);
+        assert_eq!(
+            timezone::local(task.completed_at.unwrap()).date(),
+            day("2026-10-17")
+        );
+        assert_eq!(timezone::local(task.created_at).date(), day("2026-10-01"));
+    }
+
+    #[test]
+    fn skips_lines_without_a_title() {
+        assert!(parse_line("").is_none());
+        assert!(parse_line("   ").is_none());
+        assert!(parse_line("(B) +project @context due:2026-10-20").is_none());
+    }
+
+    #[test]
+    fn keeps_what_it_cannot_read_in_the_title() {
+        let task = parse_line("(a) 2026-13-01 Fix due:+29000-01-01 due:soon pri:").unwrap();
+        assert_eq!(
+            task.title,
+            "(a) 2026-13-01 Fix due:+29000-01-01 due:soon pri:"
+        );
+        assert_eq!(task.priority, TaskPriority::Medium);
+        assert_eq!(task.due_date, None);
+        assert_eq!(parse_day("+29000-01-01"), None);
+    }
+
+    #[test]
+    fn matches_multi_line_titles_by_their_line() {
+        let db = Database::open_in_memory().unwrap();
+        let task = Task::new(
+            "Plan\n  the   offsite".into(),
+            String::new(),
+            TaskPriority::Medium,
+            10,
+            None,
+        );
+        db.create_task(&task).unwrap();
+
+        let line = format_line(&task);
+        let incoming = read_todotxt(line.replace("(B)", "(A)").as_bytes()).unwrap();
+        let summary = import_todotxt(&db, incoming, false).unwrap();
+        assert_eq!((summary.created, summary.updated), (0, 1));
+        let task = db.get_task(&task.id).unwrap().unwrap();
+        assert_eq!(task.title, "Plan\n  the   offsite");
+        assert_eq!(task.priority, TaskPriority::High);
+    }
+
+    #[test]
+    fn projects_with_spaces_survive_the_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+        let mut task = Task::new(
+            "Book venue".into(),
+            String::new(),
+            TaskPriority::Medium,
+            10,
+            None,
+        );
+        task.project = Some("team  offsite".into());
+        db.create_task(&task).unwrap();
+
+        let mut out = Vec::new();
+        write_todotxt(&db, &mut out).unwrap();
+        let incoming = read_todotxt(out.as_slice()).unwrap();
+        assert_eq!(incoming[0].title, "Book venue");
+        assert_eq!(incoming[0].project.as_deref(), Some("team-offsite"));
+
+        let summary = import_todotxt(&db, incoming, false).unwrap();
+        assert_eq!(summary.unchanged, 1);
+        let task = db.get_task(&task.id).unwrap().unwrap();
+        assert_eq!(task.project.as_deref(), Some("team  offsite"));
+    }
+}
@@ -1,3 +1,4 @@
+use clap::Parser;
 use color_eyre::eyre::Result;
 use crossterm::{
     event::{
@@ -10,18 +11,27 @@ use ratatui::{backend::CrosstermBackend, Terminal};
 use std::{io, time::Duration};
 
 mod app;
+mod cli;
 mod db;
 mod inputs;
+mod interop;
 mod ui;
 mod utils;
 
-use crate::app::{App, FormField, InputMode};
+use crate::app::{App, CurrentView, FormField, InputMode};
+use crate::cli::Cli;
 use crate::db::models::TaskPriority;
 
 fn main() -> Result<()> {
     // 1. Setup Error Handling
     color_eyre::install()?;
 
+    // Headless subcommands skip the TUI entirely
+    let cli = Cli::parse();
+    if let Some(command) = cli.command {
+        return cli::run(command);
+    }
+
     // 2. Setup Terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -55,11 +65,22 @@ fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
 ) -> io::Result<()> {
+    let mut tick_error: Option<String> = None;
     loop {
         terminal.draw(|f| ui::draw(f, app))?;
 
-        // Handle Timer Tick
-        app.on_tick();
+        // Handle Timer Tick. It runs every 250ms, so a failure that keeps
+        // happening is only reported once.
+        match app.on_tick() {
+            Ok(()) => tick_error = None,
+            Err(e) => {
+                let message = format!("Error: {}", e);
+                if tick_error.as_ref() != Some(&message) {
+                    app.status_message = Some(message.clone());
+                    tick_error = Some(message);
+                }
+            }
+        }
 
         if event::poll(Duration::from_millis(250))? {
             if let Event::Key(key) = event::read()? {
@@ -81,41 +102,166 @@ fn run_app<B: ratatui::backend::Backend>(
                     continue;
                 }
 
+                app.status_message = None;
+
                 match app.input_mode {
                     InputMode::Normal => match key.code {
                         KeyCode::Char('?') => app.show_help = !app.show_help,
                         KeyCode::Char('T') => app.next_theme(),
                         KeyCode::Char('q') => return Ok(()),
                         KeyCode::Tab => app.cycle_view(),
-                        KeyCode::Char('n') => {
-                            app.editing_task_id = None;
-                            app.task_form = crate::app::TaskForm::default();
-                            app.input_mode = InputMode::Editing;
-                        }
+                        KeyCode::Char('n') => app.start_new_task(),
                         KeyCode::Char('e') => app.start_editing(),
+                        KeyCode::Char('+') if app.current_view == CurrentView::Focus => {
+                            if let Err(e) = app.extend_phase() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('+') => app.start_checklist_input(),
+                        KeyCode::Char('s') => {
+                            if let Err(e) = app.skip_phase() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        // Inspector: j/k/SPACE/d act on the checklist
+                        KeyCode::Char('j') | KeyCode::Down if app.is_inspecting => {
+                            app.next_checklist_item()
+                        }
+                        KeyCode::Char('k') | KeyCode::Up if app.is_inspecting => {
+                            app.previous_checklist_item()
+                        }
+                        KeyCode::Char(' ') if app.is_inspecting => {
+                            if let Err(e) = app.toggle_checklist_item() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('d') if app.is_inspecting => {
+                            if let Err(e) = app.delete_checklist_item() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        // Calendar: move across days and between a day's tasks
+                        KeyCode::Char('h') | KeyCode::Left
+                            if app.current_view == CurrentView::Calendar =>
+                        {
+                            app.move_calendar_cursor(-1)
+                        }
+                        KeyCode::Char('l') | KeyCode::Right
+                            if app.current_view == CurrentView::Calendar =>
+                        {
+                            app.move_calendar_cursor(1)
+                        }
+                        KeyCode::Char('j') | KeyCode::Down
+                            if app.current_view == CurrentView::Calendar =>
+                        {
+                            app.move_calendar_cursor(7)
+                        }
+                        KeyCode::Char('k') | KeyCode::Up
+                            if app.current_view == CurrentView::Calendar =>
+                        {
+                            app.move_calendar_cursor(-7)
+                        }
+                        KeyCode::Char('J') | KeyCode::Char('K')
+                            if app.current_view == CurrentView::Calendar =>
+                        {
+                            app.step_calendar_task(key.code == KeyCode::Char('J'))
+                        }
+                        KeyCode::Char('[') | KeyCode::Char(']')
+                            if app.current_view == CurrentView::Calendar =>
+                        {
+                            app.page_calendar(key.code == KeyCode::Char(']'))
+                        }
+                        KeyCode::Char('v') if app.current_view == CurrentView::Calendar => {
+                            app.toggle_calendar_layout()
+                        }
+                        KeyCode::Char('g') if app.current_view == CurrentView::Calendar => {
+                            app.calendar_today()
+                        }
+                        KeyCode::Char('m') => {
+                            if let Err(e) = app.move_calendar_task() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
                         KeyCode::Char('j') | KeyCode::Down => app.next_item(),
                         KeyCode::Char('k') | KeyCode::Up => app.previous_item(),
                         KeyCode::Char('l') | KeyCode::Right => app.next_kanban_col(),
                         KeyCode::Char('h') | KeyCode::Left => app.prev_kanban_col(),
-                        KeyCode::Char('t') => app.toggle_timer(),
-                        KeyCode::Char('r') => app.reset_timer(),
+                        KeyCode::Char('H') | KeyCode::Char('L') => {
+                            if let Err(e) = app.move_kanban_task(key.code == KeyCode::Char('L')) {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('J') | KeyCode::Char('K') => {
+                            if let Err(e) = app.reorder_kanban_task(key.code == KeyCode::Char('J'))
+                            {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('u') => {
+                            if let Err(e) = app.undo() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            if let Err(e) = app.redo() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('a') => {
+                            if let Err(e) = app.toggle_archive() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('r') if app.current_view == CurrentView::Trash => {
+                            if let Err(e) = app.restore_trashed_task() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('x') if app.current_view == CurrentView::Trash => {
+                            if let Err(e) = app.purge_trashed_task() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('t') => {
+                            if let Err(e) = app.toggle_timer() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('r') => {
+                            if let Err(e) = app.reset_timer() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('c') => {
+                            if let Err(e) = app.toggle_clock() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('z') => {
+                            if let Err(e) = app.snooze_selected() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
                         KeyCode::Char(' ') => {
                             if let Err(e) = app.toggle_status() {
-                                eprintln!("Error: {}", e);
+                                app.status_message = Some(format!("Error: {}", e));
                             }
                         }
                         KeyCode::Char('d') | KeyCode::Delete => {
                             if let Err(e) = app.delete_current_task() {
-                                eprintln!("Error: {}", e);
+                                app.status_message = Some(format!("Error: {}", e));
                             }
                         }
-                        KeyCode::Char('/') => app.input_mode = InputMode::Search,
-                        KeyCode::Enter => app.toggle_inspector(),
-                        KeyCode::Esc => {
-                            if app.is_inspecting {
-                                app.toggle_inspector();
+                        KeyCode::Char('b') => {
+                            if let Err(e) = app.link_dependency() {
+                                app.status_message = Some(format!("Error: {}", e));
                             }
                         }
+                        KeyCode::Char('/') => app.input_mode = InputMode::Search,
+                        KeyCode::Enter => app.toggle_inspector(),
+                        KeyCode::Esc if app.link_source.is_some() => app.link_source = None,
+                        KeyCode::Esc if app.calendar.moving.is_some() => app.calendar.moving = None,
+                        KeyCode::Esc if app.is_inspecting => app.toggle_inspector(),
                         _ => {}
                     },
                     InputMode::Editing => match key.code {
@@ -126,7 +272,10 @@ fn run_app<B: ratatui::backend::Backend>(
                                 FormField::Title => FormField::Priority,
                                 FormField::Priority => FormField::XP,
                                 FormField::XP => FormField::DueDate,
-                                FormField::DueDate => FormField::Description,
+                                FormField::DueDate => FormField::Repeat,
+                                FormField::Repeat => FormField::Project,
+                                FormField::Project => FormField::Tags,
+                                FormField::Tags => FormField::Description,
                                 FormField::Description => FormField::Title,
                             };
                         }
@@ -136,7 +285,10 @@ fn run_app<B: ratatui::backend::Backend>(
                                 FormField::Priority => FormField::Title,
                                 FormField::XP => FormField::Priority,
                                 FormField::DueDate => FormField::XP,
-                                FormField::Description => FormField::DueDate,
+                                FormField::Repeat => FormField::DueDate,
+                                FormField::Project => FormField::Repeat,
+                                FormField::Tags => FormField::Project,
+                                FormField::Description => FormField::Tags,
                             };
                         }
                         // Priority Handling
@@ -166,10 +318,11 @@ fn run_app<B: ratatui::backend::Backend>(
                             // Or better: Tab to navigate, Ctrl+S or global Enter to save.
                             // User requested "friendly".
                             // Standard form behavior: Enter submits.
+                            // Stays open (on the Due field) if the date can't be read
                             if let Err(e) = app.save_task() {
-                                eprintln!("Error: {}", e);
+                                app.status_message = Some(format!("Error: {}", e));
+                                app.input_mode = InputMode::Normal;
                             }
-                            app.input_mode = InputMode::Normal;
                         }
                         // Text Input
                         _ => {
@@ -195,10 +348,30 @@ fn run_app<B: ratatui::backend::Backend>(
                                 FormField::DueDate => {
                                     app.task_form.due_date.input(key);
                                 }
+                                FormField::Repeat => {
+                                    app.task_form.recurrence.input(key);
+                                }
+                                FormField::Project => {
+                                    app.task_form.project.input(key);
+                                }
+                                FormField::Tags => {
+                                    app.task_form.tags.input(key);
+                                }
                                 FormField::Priority => {} // Handled above
                             }
                         }
                     },
+                    InputMode::Checklist => match key.code {
+                        KeyCode::Esc => app.input_mode = InputMode::Normal,
+                        KeyCode::Enter => {
+                            if let Err(e) = app.save_checklist_item() {
+                                app.status_message = Some(format!("Error: {}", e));
+                            }
+                        }
+                        _ => {
+                            app.checklist_input.input(key);
+                        }
+                    },
                     InputMode::Search => match key.code {
                         KeyCode::Enter | KeyCode::Esc => app.input_mode = InputMode::Normal,
                         KeyCode::Backspace => {
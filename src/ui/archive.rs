@@ -0,0 +1,82 @@
+use crate::app::App;
+use crate::ui::label_spans;
+use crate::ui::theme::get_theme;
+use crate::utils::format_local;
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    Frame,
+};
+
+pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = get_theme(app.current_theme);
+
+    if app.archive.is_empty() {
+        let text = if app.search_query.is_empty() {
+            "Nothing archived yet.\nPress 'a' on a finished task to archive it."
+        } else {
+            "No archived tasks match the search."
+        };
+        let p = Paragraph::new(text)
+            .style(Style::default().fg(theme.dimmed))
+            .alignment(ratatui::layout::Alignment::Center)
+            .block(Block::default().borders(Borders::NONE));
+        f.render_widget(p, area);
+        return;
+    }
+
+    let rows: Vec<Row> = app
+        .archive
+        .iter()
+        .map(|task| {
+            let mut title_spans = vec![Span::styled(
+                task.title.clone(),
+                Style::default().fg(theme.fg),
+            )];
+            title_spans.extend(label_spans(task, &theme));
+
+            let completed = task
+                .completed_at
+                .map(|d| format_local(d, "%Y-%m-%d"))
+                .unwrap_or_default();
+
+            Row::new(vec![
+                Cell::from("  ● ").style(Style::default().fg(theme.success)),
+                Cell::from(Line::from(title_spans)),
+                Cell::from(completed).style(Style::default().fg(theme.dimmed)),
+                Cell::from(format!("{} XP", task.xp_reward))
+                    .style(Style::default().fg(theme.secondary)),
+            ])
+            .style(Style::default().bg(theme.bg))
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(5),      // Icon
+            Constraint::Percentage(65), // Title
+            Constraint::Length(12),     // Completed
+            Constraint::Length(10),     // XP
+        ],
+    )
+    .header(
+        Row::new(vec!["", "TASK", "COMPLETED", "REWARD"])
+            .style(
+                Style::default()
+                    .fg(theme.dimmed)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .bottom_margin(1),
+    )
+    .row_highlight_style(
+        Style::default()
+            .bg(theme.selection_bg)
+            .fg(theme.selection_fg)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    f.render_stateful_widget(table, area, &mut app.archive_state);
+}
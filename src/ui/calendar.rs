@@ -0,0 +1,184 @@
+use crate::app::{App, CalendarLayout};
+use crate::db::models::{TaskPriority, TaskStatus};
+use crate::ui::theme::{get_theme, Theme};
+use crate::utils::timezone;
+use chrono::{Datelike, Duration, NaiveDate};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph},
+    Frame,
+};
+
+pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = get_theme(app.current_theme);
+    let cursor = app.calendar.cursor;
+    let week_start = cursor - Duration::days(cursor.weekday().num_days_from_monday() as i64);
+
+    let (title, first_day, weeks) = match app.calendar.layout {
+        CalendarLayout::Month => {
+            let first = cursor.with_day(1).unwrap();
+            let first_day = first - Duration::days(first.weekday().num_days_from_monday() as i64);
+            let last = (first + chrono::Months::new(1)).pred_opt().unwrap();
+            let weeks = ((last - first_day).num_days() / 7 + 1) as u32;
+            (
+                cursor.format(" %B %Y ").to_string().to_uppercase(),
+                first_day,
+                weeks,
+            )
+        }
+        CalendarLayout::Week => {
+            let end = week_start + Duration::days(6);
+            let title = format!(
+                " WEEK OF {} – {} ",
+                week_start.format("%b %d"),
+                end.format("%b %d %Y")
+            );
+            (title.to_uppercase(), week_start, 1)
+        }
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.border))
+        .title(Span::styled(
+            title,
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let day_names = ["MON", "TUE", "WED", "THU", "FRI", "SAT", "SUN"];
+    let header_cols = columns(rows[0]);
+    for (name, col) in day_names.iter().zip(header_cols.iter()) {
+        let p = Paragraph::new(Span::styled(*name, Style::default().fg(theme.dimmed)))
+            .alignment(Alignment::Center);
+        f.render_widget(p, *col);
+    }
+
+    let week_rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, weeks); weeks as usize])
+        .split(rows[1]);
+    for (w, row) in week_rows.iter().enumerate() {
+        for (d, cell) in columns(*row).iter().enumerate() {
+            let day = first_day + Duration::days((w * 7 + d) as i64);
+            draw_day(f, app, &theme, *cell, day);
+        }
+    }
+}
+
+fn columns(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Ratio(1, 7); 7])
+        .split(area)
+}
+
+fn draw_day(f: &mut Frame, app: &App, theme: &Theme, area: Rect, day: NaiveDate) {
+    let selected = day == app.calendar.cursor;
+    let in_month =
+        app.calendar.layout == CalendarLayout::Week || day.month() == app.calendar.cursor.month();
+
+    let number_style = if day == timezone::today() {
+        Style::default()
+            .fg(theme.bg)
+            .bg(theme.accent)
+            .add_modifier(Modifier::BOLD)
+    } else if in_month {
+        Style::default().fg(theme.fg)
+    } else {
+        Style::default().fg(theme.dimmed)
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(if selected {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        })
+        .border_style(Style::default().fg(if selected { theme.accent } else { theme.border }))
+        .title(Span::styled(format!(" {} ", day.day()), number_style));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let tasks = app.calendar_tasks(day);
+    let height = inner.height as usize;
+    if height == 0 {
+        return;
+    }
+    // A crowded day saves its last line for "+N more" and scrolls to keep
+    // the highlighted task in view
+    let visible = if tasks.len() > height {
+        height.saturating_sub(1).max(1)
+    } else {
+        height
+    };
+    let offset = if selected {
+        (app.calendar.task_index + 1).saturating_sub(visible)
+    } else {
+        0
+    };
+    let show_time = app.calendar.layout == CalendarLayout::Week;
+
+    let mut lines: Vec<Line> = tasks
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .map(|(i, task)| {
+            let icon = match task.status {
+                TaskStatus::Todo => "○ ",
+                TaskStatus::Doing => "◉ ",
+                TaskStatus::Done => "● ",
+            };
+            let time = task
+                .due_date
+                .map(timezone::due_parts)
+                .filter(|(_, time)| show_time && *time != timezone::end_of_day())
+                .map(|(_, time)| time.format("%H:%M ").to_string())
+                .unwrap_or_default();
+
+            let mut style = match task.status {
+                TaskStatus::Done => Style::default()
+                    .fg(theme.dimmed)
+                    .add_modifier(Modifier::CROSSED_OUT),
+                _ => Style::default().fg(match task.priority {
+                    TaskPriority::High => theme.error,
+                    TaskPriority::Medium => theme.warning,
+                    TaskPriority::Low => theme.success,
+                }),
+            };
+            if task.status == TaskStatus::Doing {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if app.calendar.moving.as_deref() == Some(task.id.as_str()) {
+                style = style.add_modifier(Modifier::REVERSED);
+            } else if selected && i == app.calendar.task_index {
+                style = style.bg(theme.selection_bg).fg(theme.selection_fg);
+            }
+            Line::from(Span::styled(
+                format!("{}{}{}", icon, time, task.title),
+                style,
+            ))
+        })
+        .collect();
+
+    let hidden = tasks.len().saturating_sub(offset + visible);
+    if hidden > 0 && height > 1 {
+        lines.push(Line::from(Span::styled(
+            format!("+{} more", hidden),
+            Style::default().fg(theme.dimmed),
+        )));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
@@ -1,6 +1,9 @@
 use crate::app::App;
 use crate::db::models::{TaskPriority, TaskStatus};
 use crate::ui::theme::get_theme;
+use crate::ui::{checklist_lines, label_spans};
+use crate::utils::{format_due, format_duration, format_local};
+use chrono::Utc;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
@@ -38,11 +41,15 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
         return;
     }
 
+    let now = Utc::now();
     let rows: Vec<Row> = app
         .tasks
         .iter()
         .map(|task| {
+            let overdue = task.is_overdue(now);
+            let blocked = app.blocked_ids.contains(&task.id);
             let (icon, color) = match task.status {
+                _ if blocked => ("🔒", theme.dimmed),
                 TaskStatus::Todo => ("○", theme.fg),
                 TaskStatus::Doing => ("◉", theme.warning),
                 TaskStatus::Done => ("●", theme.success),
@@ -52,6 +59,12 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
                 Style::default()
                     .fg(theme.dimmed)
                     .add_modifier(Modifier::CROSSED_OUT)
+            } else if blocked {
+                Style::default().fg(theme.dimmed)
+            } else if overdue {
+                Style::default()
+                    .fg(theme.error)
+                    .add_modifier(Modifier::BOLD)
             } else {
                 Style::default().fg(theme.fg).add_modifier(Modifier::BOLD)
             };
@@ -65,13 +78,48 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
 
             let due_str = task
                 .due_date
-                .map(|d| d.format(" %b %d").to_string())
+                .map(|d| format_due(d, " %b %d"))
                 .unwrap_or_default();
 
+            let repeat_marker = if task.recurrence.is_some() {
+                " ↻"
+            } else {
+                ""
+            };
+
+            let mut title_spans = vec![Span::styled(
+                format!(
+                    "{}{}{}{}",
+                    task.title, priority_marker, due_str, repeat_marker
+                ),
+                title_style,
+            )];
+            if app.is_clocked_in(&task.id) {
+                title_spans.push(Span::styled(" ⏱", Style::default().fg(theme.success)));
+            }
+            if let Some(count) = app.pomodoro_counts.get(&task.id) {
+                title_spans.push(Span::styled(
+                    format!(" 🍅{}", count),
+                    Style::default().fg(theme.dimmed),
+                ));
+            }
+            let (done, total) = task.checklist_progress();
+            if total > 0 {
+                let color = if done == total {
+                    theme.success
+                } else {
+                    theme.dimmed
+                };
+                title_spans.push(Span::styled(
+                    format!(" {}/{}", done, total),
+                    Style::default().fg(color),
+                ));
+            }
+            title_spans.extend(label_spans(task, &theme));
+
             Row::new(vec![
                 Cell::from(format!("  {} ", icon)).style(Style::default().fg(color)),
-                Cell::from(format!("{}{}{}", task.title, priority_marker, due_str))
-                    .style(title_style),
+                Cell::from(Line::from(title_spans)),
                 Cell::from(format!("{} XP", task.xp_reward))
                     .style(Style::default().fg(theme.secondary)),
             ])
@@ -144,6 +192,11 @@ fn draw_preview(f: &mut Frame, app: &App, area: Rect) {
                 Constraint::Length(2), // Title
                 Constraint::Length(1), // Meta
                 Constraint::Length(2), // Spacer
+                Constraint::Length(if task.subtasks.is_empty() {
+                    0
+                } else {
+                    task.subtasks.len() as u16 + 2
+                }), // Checklist
                 Constraint::Min(0),    // Description
             ]
             .as_ref(),
@@ -194,18 +247,46 @@ fn draw_preview(f: &mut Frame, app: &App, area: Rect) {
     // 3. Metadata
     let due_str = task
         .due_date
-        .map(|d| d.format(" • Due: %Y-%m-%d").to_string())
+        .map(|d| format_due(d, " • Due: %Y-%m-%d"))
+        .unwrap_or_default();
+    let repeat_str = task
+        .recurrence
+        .as_ref()
+        .map(|r| format!(" • Repeats {}", r.label()))
         .unwrap_or_default();
+    let tracked = app.tracked_seconds(&task.id);
+    let tracked_str = if app.is_clocked_in(&task.id) {
+        format!(" • ⏱ {} (running)", format_duration(tracked))
+    } else if tracked > 0 {
+        format!(" • Tracked: {}", format_duration(tracked))
+    } else {
+        String::new()
+    };
     let meta_text = format!(
-        "Reward: {} XP  •  Created: {}{}",
+        "Reward: {} XP  •  Created: {}{}{}{}",
         task.xp_reward,
-        task.created_at.format("%b %d"),
-        due_str
+        format_local(task.created_at, "%b %d"),
+        due_str,
+        repeat_str,
+        tracked_str
     );
     let meta = Paragraph::new(meta_text).style(Style::default().fg(theme.dimmed));
     f.render_widget(meta, chunks[2]);
 
-    // 4. Description
+    // 4. Checklist
+    if !task.subtasks.is_empty() {
+        let (done, total) = task.checklist_progress();
+        let mut lines = vec![Line::from(Span::styled(
+            format!("Checklist {}/{}", done, total),
+            Style::default()
+                .fg(theme.secondary)
+                .add_modifier(Modifier::BOLD),
+        ))];
+        lines.extend(checklist_lines(task, &theme, None));
+        f.render_widget(Paragraph::new(lines), chunks[4]);
+    }
+
+    // 5. Description
     let desc = if task.description.is_empty() {
         "No details provided."
     } else {
@@ -214,5 +295,5 @@ fn draw_preview(f: &mut Frame, app: &App, area: Rect) {
     let description = Paragraph::new(desc)
         .style(Style::default().fg(theme.fg))
         .wrap(Wrap { trim: true });
-    f.render_widget(description, chunks[4]);
+    f.render_widget(description, chunks[5]);
 }
@@ -1,6 +1,7 @@
 use crate::app::App;
-use crate::db::models::TaskPriority;
+use crate::db::models::{FocusPhase, TaskPriority};
 use crate::ui::theme::get_theme;
+use crate::utils::format_duration;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
@@ -40,7 +41,7 @@ pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
         .split(vertical_center);
 
     // --- 1. BIG TIMER ---
-    let remaining = app.focus_state.remaining_sec;
+    let remaining = app.focus_state.remaining();
     let mins = remaining / 60;
     let secs = remaining % 60;
 
@@ -65,9 +66,14 @@ pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
         );
     }
 
-    let timer_style = if app.focus_state.is_running {
+    let on_break = app.focus_state.phase != FocusPhase::Work;
+    let timer_style = if app.focus_state.is_running() {
         Style::default()
-            .fg(theme.success)
+            .fg(if on_break {
+                theme.secondary
+            } else {
+                theme.success
+            })
             .add_modifier(Modifier::BOLD)
     } else {
         Style::default()
@@ -82,7 +88,12 @@ pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Thick)
-                .title(" SESSION TIMER ")
+                .title(format!(
+                    " {} • ROUND {}/{} ",
+                    app.focus_state.phase.label(),
+                    app.focus_state.round,
+                    app.focus_config.long_break_every
+                ))
                 .title_alignment(Alignment::Center)
                 .border_style(Style::default().fg(theme.accent)),
         );
@@ -122,7 +133,17 @@ pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
     let task_inner = task_block.inner(chunks[4]);
     f.render_widget(task_block, chunks[4]);
 
-    if let Some(index) = app.table_state.selected() {
+    // The task of the session under way, otherwise whatever the dashboard
+    // selects (which is what `t` would start on)
+    let timer = &app.focus_state;
+    let active = timer
+        .session_task
+        .as_ref()
+        .filter(|_| timer.is_running() || timer.session_start.is_some())
+        .and_then(|id| app.tasks.iter().position(|t| &t.id == id))
+        .or(app.table_state.selected());
+
+    if let Some(index) = active {
         if let Some(task) = app.tasks.get(index) {
             let info_layout = Layout::default()
                 .direction(Direction::Vertical)
@@ -164,6 +185,21 @@ pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
                     format!("({} XP)", task.xp_reward),
                     Style::default().fg(theme.dimmed),
                 ),
+                Span::styled(
+                    format!(
+                        "  🍅 {}",
+                        app.pomodoro_counts.get(&task.id).copied().unwrap_or(0)
+                    ),
+                    Style::default().fg(theme.dimmed),
+                ),
+                Span::styled(
+                    format!("  ⏱ {}", format_duration(app.tracked_seconds(&task.id))),
+                    Style::default().fg(if app.is_clocked_in(&task.id) {
+                        theme.success
+                    } else {
+                        theme.dimmed
+                    }),
+                ),
             ]);
 
             f.render_widget(
@@ -1,9 +1,11 @@
 use crate::app::{App, FormField};
 use crate::db::models::TaskPriority;
 use crate::ui::theme::get_theme;
+use crate::utils::format_due;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
+    text::Line,
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
     Frame,
 };
@@ -41,7 +43,8 @@ pub fn draw_form_modal(f: &mut Frame, app: &mut App) {
             [
                 Constraint::Length(3), // Title
                 Constraint::Length(3), // Priority
-                Constraint::Length(3), // XP | Due Date
+                Constraint::Length(3), // XP | Due Date | Repeat
+                Constraint::Length(3), // Project | Tags
                 Constraint::Min(0),    // Description
             ]
             .as_ref(),
@@ -85,10 +88,17 @@ pub fn draw_form_modal(f: &mut Frame, app: &mut App) {
         .style(Style::default().fg(theme.fg).add_modifier(Modifier::BOLD));
     f.render_widget(prio_widget, chunks[1]);
 
-    // 3. XP & Due Date
+    // 3. XP, Due Date & Repeat
     let row3 = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+        .constraints(
+            [
+                Constraint::Percentage(20),
+                Constraint::Percentage(40),
+                Constraint::Percentage(40),
+            ]
+            .as_ref(),
+        )
         .split(chunks[2]);
 
     // XP
@@ -112,10 +122,20 @@ pub fn draw_form_modal(f: &mut Frame, app: &mut App) {
     } else {
         theme.dimmed
     };
+    // Live preview of what the field resolves to, or why it can't be read
+    let due_preview = match app.task_form.parsed_due_date() {
+        Ok(Some(due)) => Line::styled(
+            format!("{} ", format_due(due, " → %a %b %d")),
+            Style::default().fg(theme.success),
+        ),
+        Ok(None) => Line::default(),
+        Err(e) => Line::styled(format!(" ✗ {} ", e), Style::default().fg(theme.error)),
+    };
     app.task_form.due_date.set_block(
         Block::default()
             .borders(Borders::ALL)
-            .title("Due (YYYY-MM-DD)")
+            .title("Due (fri 14:00, in 3 days, eow)")
+            .title_bottom(due_preview)
             .border_style(Style::default().fg(due_border)),
     );
     app.task_form
@@ -123,7 +143,65 @@ pub fn draw_form_modal(f: &mut Frame, app: &mut App) {
         .set_style(Style::default().fg(theme.fg));
     f.render_widget(&app.task_form.due_date, row3[1]);
 
-    // 4. Description Input
+    // Repeat
+    let repeat_border = if app.task_form.active_field == FormField::Repeat {
+        theme.accent
+    } else {
+        theme.dimmed
+    };
+    let repeat_error = match app.task_form.parsed_recurrence() {
+        Err(e) => Line::styled(format!(" ✗ {} ", e), Style::default().fg(theme.error)),
+        Ok(_) => Line::default(),
+    };
+    app.task_form.recurrence.set_block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Repeat")
+            .title_bottom(repeat_error)
+            .border_style(Style::default().fg(repeat_border)),
+    );
+    app.task_form
+        .recurrence
+        .set_style(Style::default().fg(theme.fg));
+    f.render_widget(&app.task_form.recurrence, row3[2]);
+
+    // 4. Project & Tags
+    let row4 = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(chunks[3]);
+
+    let project_border = if app.task_form.active_field == FormField::Project {
+        theme.accent
+    } else {
+        theme.dimmed
+    };
+    app.task_form.project.set_block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Project")
+            .border_style(Style::default().fg(project_border)),
+    );
+    app.task_form
+        .project
+        .set_style(Style::default().fg(theme.fg));
+    f.render_widget(&app.task_form.project, row4[0]);
+
+    let tags_border = if app.task_form.active_field == FormField::Tags {
+        theme.accent
+    } else {
+        theme.dimmed
+    };
+    app.task_form.tags.set_block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Tags")
+            .border_style(Style::default().fg(tags_border)),
+    );
+    app.task_form.tags.set_style(Style::default().fg(theme.fg));
+    f.render_widget(&app.task_form.tags, row4[1]);
+
+    // 5. Description Input
     let desc_border = if app.task_form.active_field == FormField::Description {
         theme.accent
     } else {
@@ -138,7 +216,35 @@ pub fn draw_form_modal(f: &mut Frame, app: &mut App) {
     app.task_form
         .description
         .set_style(Style::default().fg(theme.fg));
-    f.render_widget(&app.task_form.description, chunks[3]);
+    f.render_widget(&app.task_form.description, chunks[4]);
+}
+
+pub fn draw_checklist_modal(f: &mut Frame, app: &mut App) {
+    let theme = get_theme(app.current_theme);
+
+    let area = centered_rect(50, 20, f.area());
+    f.render_widget(Clear, area);
+
+    app.checklist_input.set_block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme.accent))
+            .title(" ADD CHECKLIST ITEM ")
+            .title_style(
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .style(Style::default().bg(theme.surface)),
+    );
+    app.checklist_input.set_style(Style::default().fg(theme.fg));
+
+    let input_area = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(area)[0];
+    f.render_widget(&app.checklist_input, input_area);
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
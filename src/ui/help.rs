@@ -30,30 +30,72 @@ pub fn draw(f: &mut Frame, app: &mut App) {
             Row::new(vec!["Global", "TAB", "Switch View"]),
             Row::new(vec!["Global", "?", "Toggle Help"]),
             Row::new(vec!["Global", "T", "Switch Theme"]),
+            Row::new(vec!["Global", "u", "Undo"]),
+            Row::new(vec!["Global", "Ctrl+R", "Redo"]),
             Row::new(vec!["Global", "q", "Quit"]),
         ];
 
         match app.current_view {
             crate::app::CurrentView::Dashboard => {
                 rows.extend(vec![
-                    Row::new(vec!["Dashboard", "n", "New Task (!h/!m/!l for priority)"]),
+                    Row::new(vec!["Dashboard", "n", "New Task (!h/!m/!l, due:fri)"]),
                     Row::new(vec!["Dashboard", "e", "Edit Selected Task"]),
                     Row::new(vec!["Dashboard", "d", "Delete Selected Task"]),
                     Row::new(vec!["Dashboard", "SPACE", "Toggle Status"]),
-                    Row::new(vec!["Dashboard", "/", "Search Mode"]),
+                    Row::new(vec!["Dashboard", "/", "Search (project:x tag:y is:ready)"]),
+                    Row::new(vec!["Dashboard", "b", "Block By (press twice)"]),
+                    Row::new(vec!["Dashboard", "a", "Archive Finished Task"]),
+                    Row::new(vec!["Dashboard", "c", "Clock In/Out"]),
+                    Row::new(vec!["Dashboard", "z", "Snooze Reminders"]),
                     Row::new(vec!["Dashboard", "j/k", "Navigate List"]),
+                    Row::new(vec!["Dashboard", "+", "Add Checklist Item"]),
+                    Row::new(vec!["Dashboard", "Enter", "Inspector (SPC: Tick Item)"]),
                 ]);
             }
             crate::app::CurrentView::Kanban => {
                 rows.extend(vec![
                     Row::new(vec!["Kanban", "h/l", "Switch Column"]),
                     Row::new(vec!["Kanban", "j/k", "Navigate Tasks"]),
+                    Row::new(vec!["Kanban", "H/L", "Move Card Left/Right"]),
+                    Row::new(vec!["Kanban", "J/K", "Reorder Card"]),
+                ]);
+            }
+            crate::app::CurrentView::Calendar => {
+                rows.extend(vec![
+                    Row::new(vec!["Calendar", "h/l", "Previous/Next Day"]),
+                    Row::new(vec!["Calendar", "j/k", "Next/Previous Week"]),
+                    Row::new(vec!["Calendar", "J/K", "Select Task on Day"]),
+                    Row::new(vec!["Calendar", "[/]", "Previous/Next Month (Week)"]),
+                    Row::new(vec!["Calendar", "v", "Month/Week Layout"]),
+                    Row::new(vec!["Calendar", "g", "Jump to Today"]),
+                    Row::new(vec!["Calendar", "n", "New Task Due That Day"]),
+                    Row::new(vec![
+                        "Calendar",
+                        "m",
+                        "Move Task (press again on the new day)",
+                    ]),
                 ]);
             }
             crate::app::CurrentView::Focus => {
                 rows.extend(vec![
                     Row::new(vec!["Focus", "t", "Start/Pause Timer"]),
-                    Row::new(vec!["Focus", "r", "Reset Timer"]),
+                    Row::new(vec!["Focus", "s", "Skip to Next Phase"]),
+                    Row::new(vec!["Focus", "+", "Add 5 Minutes"]),
+                    Row::new(vec!["Focus", "r", "Reset Current Phase"]),
+                ]);
+            }
+            crate::app::CurrentView::Archive => {
+                rows.extend(vec![
+                    Row::new(vec!["Archive", "j/k", "Navigate Tasks"]),
+                    Row::new(vec!["Archive", "a", "Unarchive Task"]),
+                    Row::new(vec!["Archive", "/", "Search"]),
+                ]);
+            }
+            crate::app::CurrentView::Trash => {
+                rows.extend(vec![
+                    Row::new(vec!["Trash", "j/k", "Navigate Tasks"]),
+                    Row::new(vec!["Trash", "r", "Restore Task"]),
+                    Row::new(vec!["Trash", "x", "Delete Forever"]),
                 ]);
             }
             crate::app::CurrentView::Analytics | crate::app::CurrentView::Splash => {}
@@ -1,14 +1,20 @@
 use crate::app::App;
+use crate::db::models::{Task, TaskCommit, TaskStatus};
+use crate::ui::checklist_lines;
 use crate::ui::theme::get_theme;
+use crate::utils::{format_duration, format_local};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
-    text::Span,
+    text::{Line, Span},
     widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
+use std::path::Path;
+
+/// How many of the newest linked commits are listed; the header counts them all.
+const MAX_COMMITS: usize = 5;
 
-#[allow(dead_code)]
 pub fn draw(f: &mut Frame, app: &App) {
     let theme = get_theme(app.current_theme);
 
@@ -36,15 +42,24 @@ pub fn draw(f: &mut Frame, app: &App) {
     let inner_area = block.inner(area);
     f.render_widget(block, area);
 
+    let commits = app
+        .task_commits
+        .get(&task.id)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Length(1), // Title
-                Constraint::Length(1), // Separator
-                Constraint::Length(1), // Metadata
-                Constraint::Length(1), // Separator
-                Constraint::Min(0),    // Description
+                Constraint::Length(1),                       // Title
+                Constraint::Length(1),                       // Separator
+                Constraint::Length(1),                       // Metadata
+                Constraint::Length(blockers_height(task)),   // Blocked by
+                Constraint::Length(1),                       // Separator
+                Constraint::Length(checklist_height(task)),  // Checklist
+                Constraint::Length(commits_height(commits)), // Commits
+                Constraint::Min(0),                          // Description
             ]
             .as_ref(),
         )
@@ -62,15 +77,87 @@ pub fn draw(f: &mut Frame, app: &App) {
     // 2. Metadata
     let status_str = format!("{}", task.status);
     let meta = format!(
-        "Status: {} | XP Reward: {} | Created: {}",
+        "Status: {} | XP Reward: {} | Created: {} | Tracked: {}{} | Pomodoros: {}",
         status_str,
         task.xp_reward,
-        task.created_at.format("%Y-%m-%d %H:%M")
+        format_local(task.created_at, "%Y-%m-%d %H:%M"),
+        format_duration(app.tracked_seconds(&task.id)),
+        if app.is_clocked_in(&task.id) {
+            " ⏱"
+        } else {
+            ""
+        },
+        app.pomodoro_counts.get(&task.id).copied().unwrap_or(0)
     );
     let metadata = Paragraph::new(Span::styled(meta, Style::default().fg(theme.dimmed)));
     f.render_widget(metadata, chunks[2]);
 
-    // 3. Description
+    if !task.blocked_by.is_empty() {
+        let blockers: Vec<Span> = task
+            .blocked_by
+            .iter()
+            .filter_map(|id| app.blocking_tasks.get(id))
+            .map(|t| {
+                let style = if t.status == TaskStatus::Done {
+                    Style::default()
+                        .fg(theme.dimmed)
+                        .add_modifier(Modifier::CROSSED_OUT)
+                } else {
+                    Style::default().fg(theme.warning)
+                };
+                Span::styled(format!(" {}", t.title), style)
+            })
+            .collect();
+        let mut spans = vec![Span::styled(
+            "🔒 Blocked by:",
+            Style::default().fg(theme.dimmed),
+        )];
+        spans.extend(blockers);
+        f.render_widget(Paragraph::new(Line::from(spans)), chunks[3]);
+    }
+
+    // 3. Checklist
+    if !task.subtasks.is_empty() {
+        let (done, total) = task.checklist_progress();
+        let mut lines = vec![Line::from(Span::styled(
+            format!("CHECKLIST {}/{}", done, total),
+            Style::default()
+                .fg(theme.secondary)
+                .add_modifier(Modifier::BOLD),
+        ))];
+        lines.extend(checklist_lines(task, &theme, Some(app.checklist_index)));
+        f.render_widget(Paragraph::new(lines), chunks[5]);
+    }
+
+    // 4. Linked commits
+    if !commits.is_empty() {
+        let mut lines = vec![Line::from(Span::styled(
+            format!("COMMITS {}", commits.len()),
+            Style::default()
+                .fg(theme.secondary)
+                .add_modifier(Modifier::BOLD),
+        ))];
+        lines.extend(commits.iter().take(MAX_COMMITS).map(|c| {
+            let repo = Path::new(&c.repo)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", &c.hash[..7.min(c.hash.len())]),
+                    Style::default().fg(theme.warning),
+                ),
+                Span::styled(c.summary.clone(), Style::default().fg(theme.fg)),
+                Span::styled(
+                    format!("  {} {}", repo, format_local(c.committed_at, "%Y-%m-%d")),
+                    Style::default().fg(theme.dimmed),
+                ),
+            ])
+        }));
+        f.render_widget(Paragraph::new(lines), chunks[6]);
+    }
+
+    // 5. Description
     let desc_text = if task.description.is_empty() {
         "No description provided."
     } else {
@@ -81,10 +168,33 @@ pub fn draw(f: &mut Frame, app: &App) {
         .wrap(Wrap { trim: true })
         .style(Style::default().fg(theme.fg));
 
-    f.render_widget(description, chunks[4]);
+    f.render_widget(description, chunks[7]);
+}
+
+fn blockers_height(task: &Task) -> u16 {
+    if task.blocked_by.is_empty() {
+        0
+    } else {
+        1
+    }
+}
+
+fn checklist_height(task: &Task) -> u16 {
+    if task.subtasks.is_empty() {
+        0
+    } else {
+        task.subtasks.len() as u16 + 2
+    }
+}
+
+fn commits_height(commits: &[TaskCommit]) -> u16 {
+    if commits.is_empty() {
+        0
+    } else {
+        commits.len().min(MAX_COMMITS) as u16 + 2
+    }
 }
 
-#[allow(dead_code)]
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -1,5 +1,6 @@
 use crate::app::App;
 use crate::db::models::TaskStatus;
+use crate::ui::label_spans;
 use crate::ui::theme::get_theme;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
@@ -31,10 +32,20 @@ fn draw_col(f: &mut Frame, app: &mut App, area: Rect, status: TaskStatus, title:
     let theme = get_theme(app.current_theme);
 
     let tasks: Vec<ListItem> = app
-        .tasks
-        .iter()
-        .filter(|t| t.status == status)
-        .map(|t| ListItem::new(Line::from(t.title.clone())))
+        .kanban_tasks(&status)
+        .into_iter()
+        .map(|t| {
+            let mut spans = if app.blocked_ids.contains(&t.id) {
+                vec![Span::styled(
+                    format!("🔒 {}", t.title),
+                    Style::default().fg(theme.dimmed),
+                )]
+            } else {
+                vec![Span::raw(t.title.clone())]
+            };
+            spans.extend(label_spans(t, &theme));
+            ListItem::new(Line::from(spans))
+        })
         .collect();
 
     let is_focused = app.kanban_state.focused_col == idx;
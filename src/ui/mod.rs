@@ -1,5 +1,6 @@
 use crate::app::{App, CurrentView, InputMode};
-use crate::ui::theme::get_theme;
+use crate::db::models::Task;
+use crate::ui::theme::{get_theme, label_color, Theme};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
@@ -9,6 +10,8 @@ use ratatui::{
 };
 
 mod analytics;
+mod archive;
+mod calendar;
 mod dashboard;
 mod focus;
 mod form;
@@ -18,6 +21,7 @@ mod kanban;
 mod quit;
 mod splash;
 pub mod theme;
+mod trash;
 
 pub fn draw(f: &mut Frame, app: &mut App) {
     let theme = get_theme(app.current_theme);
@@ -46,18 +50,29 @@ pub fn draw(f: &mut Frame, app: &mut App) {
     match app.current_view {
         CurrentView::Dashboard => dashboard::draw(f, app, content_area),
         CurrentView::Kanban => kanban::draw(f, app, content_area),
+        CurrentView::Calendar => calendar::draw(f, app, content_area),
         CurrentView::Focus => focus::draw(f, app, content_area),
         CurrentView::Analytics => analytics::draw(f, app, content_area),
+        CurrentView::Archive => archive::draw(f, app, content_area),
+        CurrentView::Trash => trash::draw(f, app, content_area),
         CurrentView::Splash => splash::draw(f, app, f.area()),
     }
 
     draw_status_bar(f, app, layout[2]);
 
     // 3. Modals (Overlays)
+    if app.is_inspecting && app.current_view == CurrentView::Dashboard {
+        inspector::draw(f, app);
+    }
+
     if app.input_mode == InputMode::Editing {
         form::draw_form_modal(f, app);
     }
 
+    if app.input_mode == InputMode::Checklist {
+        form::draw_checklist_modal(f, app);
+    }
+
     // Help Overlay
     if app.show_help {
         help::draw(f, app);
@@ -78,7 +93,7 @@ fn draw_header_tabs(f: &mut Frame, app: &App, area: Rect) {
             [
                 Constraint::Length(20),
                 Constraint::Min(0),
-                Constraint::Length(30),
+                Constraint::Length(44),
             ]
             .as_ref(),
         )
@@ -100,7 +115,15 @@ fn draw_header_tabs(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(logo, chunks[0]);
 
     // Tabs
-    let titles = vec![" DASHBOARD ", " KANBAN ", " FOCUS ", " ANALYTICS "];
+    let titles = vec![
+        " DASHBOARD ",
+        " KANBAN ",
+        " CALENDAR ",
+        " FOCUS ",
+        " ANALYTICS ",
+        " ARCHIVE ",
+        " TRASH ",
+    ];
     let tabs = Tabs::new(titles)
         .block(
             Block::default()
@@ -116,8 +139,11 @@ fn draw_header_tabs(f: &mut Frame, app: &App, area: Rect) {
         .select(match app.current_view {
             CurrentView::Dashboard => 0,
             CurrentView::Kanban => 1,
-            CurrentView::Focus => 2,
-            CurrentView::Analytics => 3,
+            CurrentView::Calendar => 2,
+            CurrentView::Focus => 3,
+            CurrentView::Analytics => 4,
+            CurrentView::Archive => 5,
+            CurrentView::Trash => 6,
             CurrentView::Splash => 0,
         });
     f.render_widget(tabs, chunks[1]);
@@ -128,16 +154,26 @@ fn draw_header_tabs(f: &mut Frame, app: &App, area: Rect) {
         " 🔥 {} | Today: {} | LVL {} ",
         app.streak, app.tasks_today, profile.level
     );
-    let stats = Paragraph::new(Span::styled(
+    let mut stats_spans = Vec::new();
+    if app.overdue_count > 0 {
+        stats_spans.push(Span::styled(
+            format!(" ⚠ {} overdue |", app.overdue_count),
+            Style::default()
+                .fg(theme.error)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    stats_spans.push(Span::styled(
         stats_text,
         Style::default().fg(theme.secondary),
-    ))
-    .alignment(ratatui::layout::Alignment::Right)
-    .block(
-        Block::default()
-            .borders(Borders::BOTTOM)
-            .border_style(Style::default().fg(theme.border)),
-    );
+    ));
+    let stats = Paragraph::new(Line::from(stats_spans))
+        .alignment(ratatui::layout::Alignment::Right)
+        .block(
+            Block::default()
+                .borders(Borders::BOTTOM)
+                .border_style(Style::default().fg(theme.border)),
+        );
     f.render_widget(stats, chunks[2]);
 }
 
@@ -148,18 +184,26 @@ fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
         InputMode::Normal => (" NORMAL ", theme.accent),
         InputMode::Editing => (" INSERT ", theme.success),
         InputMode::Search => (" SEARCH ", theme.warning),
+        InputMode::Checklist => (" ITEM ", theme.success),
     };
 
     let hints = match app.input_mode {
         InputMode::Editing => "TAB: Next • Enter: Save • Esc: Cancel",
+        InputMode::Checklist => "Enter: Add Item • Esc: Cancel",
+        _ if app.is_inspecting => "j/k: Item • SPC: Tick • +: Add • d: Remove • Esc: Close",
         _ => match app.current_view {
             CurrentView::Dashboard => {
-                "n: New • e: Edit • d: Delete • SPC: Status • /: Search • T: Theme"
+                "n: New • e: Edit • d: Delete • SPC: Status • c: Clock • z: Snooze • a: Archive • b: Block • u: Undo • /: Search"
             }
-            CurrentView::Kanban => "h/l: Col • j/k: Task • T: Theme",
-            CurrentView::Focus => "t: Timer • r: Reset • T: Theme",
+            CurrentView::Kanban => "h/l: Col • j/k: Task • H/L: Move • J/K: Reorder • T: Theme",
+            CurrentView::Calendar => {
+                "h/j/k/l: Day • J/K: Task • [/]: Page • v: Month/Week • g: Today • n: New • m: Move"
+            }
+            CurrentView::Focus => "t: Timer (clocks in) • s: Skip • +: +5 min • r: Reset • T: Theme",
             CurrentView::Splash => "Press Any Key",
             CurrentView::Analytics => "T: Theme",
+            CurrentView::Archive => "j/k: Task • a: Unarchive • /: Search • T: Theme",
+            CurrentView::Trash => "j/k: Task • r: Restore • x: Delete Forever • T: Theme",
         },
     };
 
@@ -172,7 +216,21 @@ fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw(" "),
-        Span::styled(hints, Style::default().fg(theme.dimmed)),
+        if app.link_source.is_some() {
+            Span::styled(
+                "Select the blocking task and press b • Esc: Cancel",
+                Style::default().fg(theme.warning),
+            )
+        } else if app.calendar.moving.is_some() {
+            Span::styled(
+                "Pick the new day and press m • Esc: Cancel",
+                Style::default().fg(theme.warning),
+            )
+        } else if let Some(message) = &app.status_message {
+            Span::styled(message.clone(), Style::default().fg(theme.warning))
+        } else {
+            Span::styled(hints, Style::default().fg(theme.dimmed))
+        },
         Span::raw(" "),
         if !app.search_query.is_empty() {
             Span::styled(
@@ -187,3 +245,52 @@ fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
 
     f.render_widget(status, area);
 }
+
+/// `@project #tag` badges shown after a task title.
+fn label_spans(task: &Task, theme: &Theme) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    if let Some(project) = &task.project {
+        spans.push(Span::styled(
+            format!(" @{}", project),
+            Style::default()
+                .fg(label_color(theme, project))
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    for tag in &task.tags {
+        spans.push(Span::styled(
+            format!(" #{}", tag),
+            Style::default().fg(label_color(theme, tag)),
+        ));
+    }
+    spans
+}
+
+/// One line per checklist item; `selected` highlights the inspector cursor.
+fn checklist_lines(task: &Task, theme: &Theme, selected: Option<usize>) -> Vec<Line<'static>> {
+    task.subtasks
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let (mark, style) = if s.done {
+                (
+                    "[x] ",
+                    Style::default()
+                        .fg(theme.dimmed)
+                        .add_modifier(Modifier::CROSSED_OUT),
+                )
+            } else {
+                ("[ ] ", Style::default().fg(theme.fg))
+            };
+            let style = if selected == Some(i) {
+                style.bg(theme.selection_bg).fg(theme.selection_fg)
+            } else {
+                style
+            };
+            Line::from(vec![
+                Span::styled(mark, Style::default().fg(theme.success)),
+                Span::styled(s.title.clone(), style),
+            ])
+        })
+        .collect()
+}
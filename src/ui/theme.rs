@@ -35,6 +35,21 @@ pub struct Theme {
     pub dimmed: Color,
 }
 
+/// Stable colour for a tag or project name, picked from the theme palette.
+pub fn label_color(theme: &Theme, name: &str) -> Color {
+    let palette = [
+        theme.accent,
+        theme.secondary,
+        theme.success,
+        theme.warning,
+        theme.error,
+    ];
+    let hash = name
+        .bytes()
+        .fold(0usize, |h, b| h.wrapping_mul(31).wrapping_add(b as usize));
+    palette[hash % palette.len()]
+}
+
 pub fn get_theme(t: ThemeType) -> Theme {
     match t {
         ThemeType::Horizon => HORIZON,
@@ -0,0 +1,75 @@
+use crate::app::App;
+use crate::ui::theme::get_theme;
+use crate::utils::format_local;
+use chrono::{Duration, Utc};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    Frame,
+};
+
+pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = get_theme(app.current_theme);
+
+    if app.trash.is_empty() {
+        let p = Paragraph::new("Trash is empty.")
+            .style(Style::default().fg(theme.dimmed))
+            .alignment(ratatui::layout::Alignment::Center)
+            .block(Block::default().borders(Borders::NONE));
+        f.render_widget(p, area);
+        return;
+    }
+
+    let rows: Vec<Row> = app
+        .trash
+        .iter()
+        .map(|task| {
+            let deleted_at = task.deleted_at.unwrap_or_else(Utc::now);
+            let purge_str = if app.trash_retention_days > 0 {
+                let left = (deleted_at + Duration::days(app.trash_retention_days) - Utc::now())
+                    .num_days()
+                    .max(0);
+                format!("in {}d", left)
+            } else {
+                "never".to_string()
+            };
+
+            Row::new(vec![
+                Cell::from("  🗑 ").style(Style::default().fg(theme.dimmed)),
+                Cell::from(task.title.clone()).style(Style::default().fg(theme.fg)),
+                Cell::from(format_local(deleted_at, "%b %d %H:%M"))
+                    .style(Style::default().fg(theme.dimmed)),
+                Cell::from(purge_str).style(Style::default().fg(theme.warning)),
+            ])
+            .style(Style::default().bg(theme.bg))
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(5),      // Icon
+            Constraint::Percentage(60), // Title
+            Constraint::Length(14),     // Deleted
+            Constraint::Length(10),     // Purge
+        ],
+    )
+    .header(
+        Row::new(vec!["", "TASK", "DELETED", "PURGE"])
+            .style(
+                Style::default()
+                    .fg(theme.dimmed)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .bottom_margin(1),
+    )
+    .row_highlight_style(
+        Style::default()
+            .bg(theme.selection_bg)
+            .fg(theme.selection_fg)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    f.render_stateful_widget(table, area, &mut app.trash_state);
+}
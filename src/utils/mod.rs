@@ -1 +1,249 @@
+pub mod timezone;
 
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+
+/// The longest reminder offset or snooze `parse_span` accepts, and the
+/// longest interval of an `every:Nd` repeat rule.
+pub const MAX_SPAN_DAYS: i64 = 365;
+
+/// Parses a due date in the user's timezone. Without a time it means the end
+/// of that day.
+///
+/// Accepts `YYYY-MM-DD`, `today`, `tomorrow`, a weekday (`fri`, `next fri`;
+/// always the next one after today), `eow` / `eom` (end of week / month) or
+/// an offset like `+3d`, `+2w`, `in 3 days`. Any of these may be followed by
+/// a time (`14:00`, `9am`, `5:30pm`); a time on its own means today.
+pub fn parse_due_date(input: &str) -> Option<DateTime<Utc>> {
+    let input = input.trim().to_lowercase();
+    let mut words: Vec<&str> = input.split_whitespace().collect();
+
+    let time = match words.last().and_then(|w| parse_time(w)) {
+        Some(time) => {
+            words.pop();
+            time
+        }
+        None => timezone::end_of_day(),
+    };
+    let today = timezone::today();
+    let date = if words.is_empty() {
+        today
+    } else {
+        parse_day(&words, today)?
+    };
+    // Later years don't survive the round trip through RFC 3339
+    if date.year() > 9999 {
+        return None;
+    }
+
+    Some(timezone::to_utc(date.and_time(time)))
+}
+
+/// Formats a due date in the user's timezone with `date_format`, adding the
+/// time of day unless it's the end-of-day default.
+pub fn format_due(due: DateTime<Utc>, date_format: &str) -> String {
+    let (date, time) = timezone::due_parts(due);
+    let mut text = date.format(date_format).to_string();
+    if time != timezone::end_of_day() {
+        text.push_str(&time.format(" %H:%M").to_string());
+    }
+    text
+}
+
+/// The first 8 characters of a task id, as `list` shows them. Cuts by
+/// character, since imported ids aren't necessarily UUIDs.
+pub fn short_id(id: &str) -> &str {
+    match id.char_indices().nth(8) {
+        Some((end, _)) => &id[..end],
+        None => id,
+    }
+}
+
+/// Formats a stored timestamp in the user's timezone.
+pub fn format_local(t: DateTime<Utc>, format: &str) -> String {
+    timezone::local(t).format(format).to_string()
+}
+
+fn parse_day(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    let date = match words {
+        ["today"] => today,
+        ["tomorrow" | "tmr" | "tmrw"] => today + Duration::days(1),
+        ["eow"] => today + Duration::days(6 - today.weekday().num_days_from_monday() as i64),
+        ["eom"] => {
+            let next_month = if today.month() == 12 {
+                NaiveDate::from_ymd_opt(today.year() + 1, 1, 1)?
+            } else {
+                NaiveDate::from_ymd_opt(today.year(), today.month() + 1, 1)?
+            };
+            next_month - Duration::days(1)
+        }
+        ["next", day] | [day] if parse_weekday(day).is_some() => {
+            let weekday = parse_weekday(day)?;
+            let ahead = (weekday.num_days_from_monday() as i64
+                - today.weekday().num_days_from_monday() as i64)
+                .rem_euclid(7);
+            today + Duration::days(if ahead == 0 { 7 } else { ahead })
+        }
+        ["in", n, unit] => today.checked_add_signed(parse_offset(&format!("{}{}", n, unit))?)?,
+        [word] => {
+            if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+                date
+            } else {
+                today.checked_add_signed(parse_offset(word.strip_prefix('+')?)?)?
+            }
+        }
+        _ => return None,
+    };
+    Some(date)
+}
+
+/// `14:00`, `9am`, `5:30pm`.
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    if let Ok(time) = NaiveTime::parse_from_str(s, "%H:%M") {
+        return Some(time);
+    }
+    let (clock, pm) = match (s.strip_suffix("am"), s.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, false),
+        (_, Some(clock)) => (clock, true),
+        _ => return None,
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    if !(1..=12).contains(&hour) {
+        return None;
+    }
+    NaiveTime::from_hms_opt(hour % 12 + if pm { 12 } else { 0 }, minute, 0)
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    match s {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tues" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thurs" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// `3d` / `3days` -> 3 days, `2w` / `2weeks` -> 14 days.
+fn parse_offset(s: &str) -> Option<Duration> {
+    let digits = s.find(|c: char| !c.is_ascii_digit())?;
+    let n: i64 = s[..digits].parse().ok()?;
+    match &s[digits..] {
+        "d" | "day" | "days" => Duration::try_days(n),
+        "w" | "week" | "weeks" => Duration::try_weeks(n),
+        _ => None,
+    }
+}
+
+/// Reminder offsets and snooze lengths: `0`, `30m`, `2h`, `1d`, `1w`, at
+/// most `MAX_SPAN_DAYS`.
+pub fn parse_span(s: &str) -> Option<Duration> {
+    let s = s.trim().to_lowercase();
+    if s == "0" {
+        return Some(Duration::zero());
+    }
+    let digits = s.find(|c: char| !c.is_ascii_digit())?;
+    let n: i64 = s[..digits].parse().ok()?;
+    let span = match &s[digits..] {
+        "m" | "min" | "mins" => Duration::try_minutes(n),
+        "h" | "hour" | "hours" => Duration::try_hours(n),
+        _ => parse_offset(&s),
+    }?;
+    (span <= Duration::days(MAX_SPAN_DAYS)).then_some(span)
+}
+
+/// `3725` -> `1h 02m`, `300` -> `5m`.
+pub fn format_duration(seconds: i64) -> String {
+    let minutes = seconds / 60;
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_relative_days() {
+        // A Sunday
+        let today = day("2026-10-18");
+        assert_eq!(parse_day(&["tomorrow"], today), Some(day("2026-10-19")));
+        assert_eq!(parse_day(&["eow"], today), Some(today));
+        assert_eq!(parse_day(&["eom"], today), Some(day("2026-10-31")));
+        assert_eq!(parse_day(&["sun"], today), Some(day("2026-10-25")));
+        assert_eq!(parse_day(&["next", "fri"], today), Some(day("2026-10-23")));
+        assert_eq!(
+            parse_day(&["in", "3", "days"], today),
+            Some(day("2026-10-21"))
+        );
+        assert_eq!(parse_day(&["+2w"], today), Some(day("2026-11-01")));
+        assert_eq!(parse_day(&["2027-02-01"], today), Some(day("2027-02-01")));
+    }
+
+    #[test]
+    fn rejects_offsets_that_overflow() {
+        let today = day("2026-10-18");
+        assert_eq!(parse_day(&["in", "99999999999999", "days"], today), None);
+        assert_eq!(parse_day(&["+99999999999999999w"], today), None);
+        assert_eq!(parse_offset("9223372036854775807d"), None);
+        assert_eq!(parse_offset("99999999999999999999d"), None);
+    }
+
+    #[test]
+    fn rejects_dates_past_year_9999() {
+        assert!(parse_due_date("+9999999d").is_none());
+        assert!(parse_due_date("in 3000000 days").is_none());
+        assert!(parse_due_date("+3d").is_some());
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse_time("14:00"), NaiveTime::from_hms_opt(14, 0, 0));
+        assert_eq!(parse_time("9am"), NaiveTime::from_hms_opt(9, 0, 0));
+        assert_eq!(parse_time("12am"), NaiveTime::from_hms_opt(0, 0, 0));
+        assert_eq!(parse_time("5:30pm"), NaiveTime::from_hms_opt(17, 30, 0));
+        assert_eq!(parse_time("13pm"), None);
+        assert_eq!(parse_time("25:00"), None);
+    }
+
+    #[test]
+    fn parses_spans() {
+        assert_eq!(parse_span("0"), Some(Duration::zero()));
+        assert_eq!(parse_span("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_span(" 2H "), Some(Duration::hours(2)));
+        assert_eq!(parse_span("1d"), Some(Duration::days(1)));
+        assert_eq!(parse_span("1w"), Some(Duration::weeks(1)));
+        assert_eq!(parse_span("365d"), Some(Duration::days(MAX_SPAN_DAYS)));
+        assert_eq!(parse_span("30"), None);
+        assert_eq!(parse_span("-1h"), None);
+        assert_eq!(parse_span("1y"), None);
+    }
+
+    #[test]
+    fn rejects_spans_that_are_too_long() {
+        assert_eq!(parse_span("366d"), None);
+        assert_eq!(parse_span("99999999d"), None);
+        assert_eq!(parse_span("99999999999999999h"), None);
+        assert_eq!(parse_span("9223372036854775807m"), None);
+        assert_eq!(parse_span("99999999999999999999m"), None);
+    }
+
+    #[test]
+    fn cuts_short_ids_by_character() {
+        assert_eq!(short_id("1a2b3c4d-5e6f"), "1a2b3c4d");
+        assert_eq!(short_id("äöüäöüäöü"), "äöüäöüäö");
+        assert_eq!(short_id("abc"), "abc");
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::{
+    DateTime, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use std::sync::OnceLock;
+
+/// The zone days are counted in: the `timezone` setting (an IANA name such as
+/// `America/Los_Angeles`) or, when it's unset, the system's.
+#[derive(Debug, Clone, Copy)]
+enum UserZone {
+    System,
+    Named(Tz),
+}
+
+static ZONE: OnceLock<UserZone> = OnceLock::new();
+
+/// Picks the zone from the `timezone` setting. Called once when the database
+/// is opened; an unknown name falls back to the system zone.
+pub fn init(setting: Option<&str>) {
+    let zone = setting
+        .and_then(|name| name.parse::<Tz>().ok())
+        .map_or(UserZone::System, UserZone::Named);
+    let _ = ZONE.set(zone);
+}
+
+pub fn is_valid(name: &str) -> bool {
+    name.parse::<Tz>().is_ok()
+}
+
+fn zone() -> UserZone {
+    *ZONE.get_or_init(|| UserZone::System)
+}
+
+/// Wall-clock time of `t` in the user's zone.
+pub fn local(t: DateTime<Utc>) -> NaiveDateTime {
+    match zone() {
+        UserZone::System => t.with_timezone(&Local).naive_local(),
+        UserZone::Named(tz) => t.with_timezone(&tz).naive_local(),
+    }
+}
+
+pub fn today() -> NaiveDate {
+    local(Utc::now()).date()
+}
+
+/// The instant a wall-clock time in the user's zone refers to. Ambiguous
+/// times take the earlier one; times skipped by DST move forward an hour.
+pub fn to_utc(naive: NaiveDateTime) -> DateTime<Utc> {
+    fn resolve<Z: TimeZone>(zone: &Z, naive: NaiveDateTime) -> DateTime<Utc> {
+        match zone.from_local_datetime(&naive) {
+            LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => t.with_timezone(&Utc),
+            LocalResult::None => resolve(zone, naive + Duration::hours(1)),
+        }
+    }
+    match zone() {
+        UserZone::System => resolve(&Local, naive),
+        UserZone::Named(tz) => resolve(&tz, naive),
+    }
+}
+
+/// Time given to due dates without one: the last second of the day.
+pub fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_opt(23, 59, 59).unwrap()
+}
+
+/// The local day and time a due date stands for. Older versions stored
+/// date-only due dates as 23:59:59 UTC; those keep their UTC day.
+pub fn due_parts(due: DateTime<Utc>) -> (NaiveDate, NaiveTime) {
+    let local = local(due);
+    if local.time() != end_of_day() && due.time() == end_of_day() {
+        (due.date_naive(), end_of_day())
+    } else {
+        (local.date(), local.time())
+    }
+}